@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use colored::Colorize;
+use humantime::format_duration;
+use inquire::{Confirm, Select, Text};
+use reqwest::Client;
+
+use crate::{
+    prompts,
+    storage::{
+        config::{retrieve_config, Config, JiraFlavour},
+        credentials::{retrieve_credentials, Credentials},
+    },
+    tempo::{
+        service::{get_approval, get_reviewers, submit_approval},
+        structs::{SubmitApprovalRequest, TimesheetApproval},
+    },
+    utils::{select_week, week_bounds},
+};
+
+fn print_approval(approval: &TimesheetApproval) {
+    let reviewer = match &approval.reviewer {
+        Some(reviewer) => reviewer
+            .display_name
+            .clone()
+            .unwrap_or(reviewer.account_id.to_string()),
+        None => "None".to_string(),
+    };
+    println!(
+        "{} - {}: {}, {} of {} logged, reviewer: {}",
+        approval.period.from,
+        approval.period.to,
+        approval.status.key.blue().underline(),
+        format_duration(Duration::from_secs(approval.time_spent_seconds)),
+        format_duration(Duration::from_secs(approval.required_seconds)),
+        reviewer.green()
+    );
+    if let Some(comment) = &approval.status.comment && !comment.is_empty() {
+        println!("Comment: {}", comment);
+    }
+}
+
+pub async fn approval_status(week: bool) -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    let selected_date = select_week(week)?;
+    show_approval(&credentials, &config, selected_date).await
+}
+
+pub async fn show_approval(
+    credentials: &Credentials,
+    config: &Config,
+    date: NaiveDate,
+) -> anyhow::Result<()> {
+    ensure_cloud(config)?;
+    let (from, to) = week_bounds(date);
+    let approval = get_approval(
+        &Client::new(),
+        config.tempo_url(),
+        &credentials.tempo_token,
+        &credentials.account_id,
+        &from.format("%Y-%m-%d").to_string(),
+        &to.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    print_approval(&approval);
+    Ok(())
+}
+
+pub async fn approval_submit(week: bool) -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    let selected_date = select_week(week)?;
+    submit_timesheet(&credentials, &config, selected_date).await
+}
+
+pub async fn submit_timesheet(
+    credentials: &Credentials,
+    config: &Config,
+    date: NaiveDate,
+) -> anyhow::Result<()> {
+    ensure_cloud(config)?;
+    let client = Client::new();
+    let (from, to) = week_bounds(date);
+    let from = from.format("%Y-%m-%d").to_string();
+    let to = to.format("%Y-%m-%d").to_string();
+    let approval = get_approval(
+        &client,
+        config.tempo_url(),
+        &credentials.tempo_token,
+        &credentials.account_id,
+        &from,
+        &to,
+    )
+    .await?;
+    print_approval(&approval);
+    if approval.status.key != "OPEN" {
+        println!("Timesheet is not open, nothing to submit!");
+        return Ok(());
+    }
+
+    let reviewers = get_reviewers(
+        &client,
+        config.tempo_url(),
+        &credentials.tempo_token,
+        &credentials.account_id,
+    )
+    .await?;
+    let reviewer_account_id = match reviewers.len() {
+        0 => {
+            println!("{}", "No reviewers available, can't submit!".red());
+            return Ok(());
+        }
+        1 => reviewers[0].account_id.to_string(),
+        _ => {
+            let options = reviewers
+                .iter()
+                .map(|reviewer| {
+                    reviewer
+                        .display_name
+                        .clone()
+                        .unwrap_or(reviewer.account_id.to_string())
+                })
+                .collect::<Vec<String>>();
+            let selected = prompts::select(Select::new("Reviewer?", options.clone()))?;
+            let index = options.iter().position(|x| *x == selected).unwrap_or(0);
+            reviewers[index].account_id.to_string()
+        }
+    };
+    if !prompts::confirm(Confirm::new(&format!("Submit {} - {} for approval? (y/n)", from, to)))? {
+        return Ok(());
+    }
+    let comment = prompts::text_skippable(Text::new("Comment?"))?.filter(|x| !x.is_empty());
+    let request = SubmitApprovalRequest {
+        comment,
+        reviewer_account_id,
+    };
+    let approval = submit_approval(
+        &client,
+        config.tempo_url(),
+        &credentials.tempo_token,
+        &credentials.account_id,
+        &from,
+        &to,
+        &request,
+    )
+    .await?;
+    print_approval(&approval);
+
+    Ok(())
+}
+
+fn ensure_cloud(config: &Config) -> anyhow::Result<()> {
+    if config.jira_flavour() == JiraFlavour::Server {
+        anyhow::bail!("Timesheet approvals are only supported on Tempo Cloud");
+    }
+    Ok(())
+}
@@ -0,0 +1,302 @@
+use reqwest::{header::CONTENT_TYPE, Client, Method, RequestBuilder, Response, StatusCode};
+
+use crate::{storage::config::JiraFlavour, tempo::structs::Worklog};
+
+use super::structs::{
+    Comment, JiraField, JiraIssue, JiraProject, JiraUser, NewWorklog, PermissionsResponse, ProjectPage,
+    SearchRequest, SearchResponse, Transition, TransitionId, TransitionRequest,
+    TransitionsResponse, WorklogComment,
+};
+
+const MAX_KEYS_PER_QUERY: usize = 50;
+const PAGE_SIZE: u32 = 100;
+const LOG_WORK_PERMISSION: &str = "WORK_ON_ISSUES";
+const EPIC_LINK_SCHEMA: &str = "com.pyxis.greenhopper.jira:gh-epic-link";
+
+// Cloud takes an email and API token on REST API 3, Server and Data Center a personal access token on REST API 2
+#[derive(Clone, Debug)]
+pub struct JiraSite {
+    pub url: String,
+    pub flavour: JiraFlavour,
+    pub email: String,
+    pub token: String,
+}
+
+impl JiraSite {
+    fn api(&self, path: &str) -> String {
+        match self.flavour {
+            JiraFlavour::Cloud => format!("{}/rest/api/3{}", self.url, path),
+            JiraFlavour::Server => format!("{}/rest/api/2{}", self.url, path),
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.flavour {
+            JiraFlavour::Cloud => request.basic_auth(&self.email, Some(&self.token)),
+            JiraFlavour::Server => request.bearer_auth(&self.token),
+        }
+    }
+
+    // Server has no token based search
+    fn search_url(&self) -> String {
+        match self.flavour {
+            JiraFlavour::Cloud => self.api("/search/jql"),
+            JiraFlavour::Server => self.api("/search"),
+        }
+    }
+}
+
+pub async fn search_issues(
+    client: &Client,
+    site: &JiraSite,
+    jql: &str,
+    fields: &[&str],
+) -> anyhow::Result<Vec<JiraIssue>> {
+    let mut issues = Vec::new();
+    let mut next_page_token: Option<String> = None;
+    loop {
+        let request = SearchRequest {
+            jql: jql.to_string(),
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            max_results: PAGE_SIZE,
+            next_page_token,
+            start_at: (site.flavour == JiraFlavour::Server).then_some(issues.len() as u32),
+        };
+        let response = site
+            .authorize(client.request(Method::POST, site.search_url()))
+            .header(CONTENT_TYPE, "application/json")
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SearchResponse>()
+            .await?;
+        let page_size = response.issues.len();
+        issues.extend(response.issues);
+        next_page_token = response.next_page_token;
+        let has_more = match site.flavour {
+            JiraFlavour::Cloud => next_page_token.is_some(),
+            JiraFlavour::Server => {
+                page_size > 0 && issues.len() < response.total.unwrap_or(0) as usize
+            }
+        };
+        if !has_more {
+            break;
+        }
+    }
+    Ok(issues)
+}
+
+// Only the first page, for the key autocompletion
+pub async fn quick_search_issues(
+    client: &Client,
+    site: &JiraSite,
+    jql: &str,
+    max_results: u32,
+) -> anyhow::Result<Vec<JiraIssue>> {
+    let request = SearchRequest {
+        jql: jql.to_string(),
+        fields: vec!["summary".to_string()],
+        max_results,
+        next_page_token: None,
+        start_at: None,
+    };
+    let response = site
+        .authorize(client.request(Method::POST, site.search_url()))
+        .header(CONTENT_TYPE, "application/json")
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<SearchResponse>()
+        .await?;
+    Ok(response.issues)
+}
+
+pub async fn get_issue(
+    client: &Client,
+    site: &JiraSite,
+    key: &str,
+    fields: &[&str],
+) -> anyhow::Result<Option<JiraIssue>> {
+    let response = site
+        .authorize(client.request(Method::GET, site.api(&format!("/issue/{}", key))))
+        .query(&[("fields", fields.join(","))])
+        .send()
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let issue = response.error_for_status()?.json::<JiraIssue>().await?;
+    Ok(Some(issue))
+}
+
+// Jira rejects the whole query when one of the keys doesn't exist, those batches are checked one by one
+pub async fn find_issues(
+    client: &Client,
+    site: &JiraSite,
+    keys: &[String],
+    fields: &[&str],
+) -> anyhow::Result<Vec<JiraIssue>> {
+    let mut issues = Vec::new();
+    for chunk in keys.chunks(MAX_KEYS_PER_QUERY) {
+        let jql = format!(
+            "key in ({})",
+            chunk
+                .iter()
+                .map(|key| format!("\"{}\"", key))
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+        match search_issues(client, site, &jql, fields).await {
+            Ok(found) => issues.extend(found),
+            Err(_) => {
+                for key in chunk {
+                    if let Some(issue) = get_issue(client, site, key, fields).await? {
+                        issues.push(issue);
+                    }
+                }
+            }
+        }
+    }
+    Ok(issues)
+}
+
+pub async fn get_projects(client: &Client, site: &JiraSite) -> anyhow::Result<Vec<JiraProject>> {
+    if site.flavour == JiraFlavour::Server {
+        let projects = site
+            .authorize(client.request(Method::GET, site.api("/project")))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<JiraProject>>()
+            .await?;
+        return Ok(projects);
+    }
+    let mut projects = Vec::new();
+    loop {
+        let page = site
+            .authorize(client.request(Method::GET, site.api("/project/search")))
+            .query(&[("startAt", projects.len() as u32), ("maxResults", PAGE_SIZE)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ProjectPage>()
+            .await?;
+        let is_last = page.is_last || page.values.is_empty();
+        projects.extend(page.values);
+        if is_last {
+            break;
+        }
+    }
+    Ok(projects)
+}
+
+// Server and Data Center link stories to epics through a custom field instead of the parent
+pub async fn get_epic_link_field(client: &Client, site: &JiraSite) -> anyhow::Result<Option<String>> {
+    let fields = site
+        .authorize(client.request(Method::GET, site.api("/field")))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<JiraField>>()
+        .await?;
+    Ok(fields
+        .into_iter()
+        .find(|field| {
+            field
+                .schema
+                .as_ref()
+                .and_then(|schema| schema.custom.as_deref())
+                .map_or(field.name == "Epic Link", |custom| custom == EPIC_LINK_SCHEMA)
+        })
+        .map(|field| field.id))
+}
+
+pub async fn can_log_work(client: &Client, site: &JiraSite, key: &str) -> anyhow::Result<bool> {
+    let response = site
+        .authorize(client.request(Method::GET, site.api("/mypermissions")))
+        .query(&[("issueKey", key), ("permissions", LOG_WORK_PERMISSION)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<PermissionsResponse>()
+        .await?;
+    Ok(response
+        .permissions
+        .get(LOG_WORK_PERMISSION)
+        .is_some_and(|permission| permission.have_permission))
+}
+
+pub async fn create_worklog(
+    client: &Client,
+    site: &JiraSite,
+    work_log: &Worklog,
+) -> anyhow::Result<Response> {
+    // Server takes plain text comments instead of documents
+    let comment = match site.flavour {
+        JiraFlavour::Cloud => WorklogComment::Document(Comment::from_text(&work_log.description)),
+        JiraFlavour::Server => WorklogComment::Text(work_log.description.to_string()),
+    };
+    let new_worklog = NewWorklog {
+        started: work_log.date.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string(),
+        time_spent_seconds: work_log.time_spent_seconds,
+        comment: (!work_log.description.is_empty()).then_some(comment),
+    };
+    let response = site
+        .authorize(client.request(
+            Method::POST,
+            site.api(&format!("/issue/{}/worklog", work_log.issue_key)),
+        ))
+        .header(CONTENT_TYPE, "application/json")
+        .json(&new_worklog)
+        .send()
+        .await?;
+    Ok(response)
+}
+
+pub async fn get_transitions(
+    client: &Client,
+    site: &JiraSite,
+    key: &str,
+) -> anyhow::Result<Vec<Transition>> {
+    let response = site
+        .authorize(client.request(Method::GET, site.api(&format!("/issue/{}/transitions", key))))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TransitionsResponse>()
+        .await?;
+    Ok(response.transitions)
+}
+
+pub async fn transition_issue(
+    client: &Client,
+    site: &JiraSite,
+    key: &str,
+    transition_id: &str,
+) -> anyhow::Result<()> {
+    let request = TransitionRequest {
+        transition: TransitionId {
+            id: transition_id.to_string(),
+        },
+    };
+    site.authorize(client.request(Method::POST, site.api(&format!("/issue/{}/transitions", key))))
+        .header(CONTENT_TYPE, "application/json")
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+pub async fn get_myself(client: &Client, site: &JiraSite) -> anyhow::Result<JiraUser> {
+    let user = site
+        .authorize(client.request(Method::GET, site.api("/myself")))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<JiraUser>()
+        .await?;
+    Ok(user)
+}
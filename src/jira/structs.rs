@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Debug)]
+pub struct SearchRequest {
+    pub jql: String,
+    pub fields: Vec<String>,
+    #[serde(rename = "maxResults")]
+    pub max_results: u32,
+    #[serde(rename = "nextPageToken", skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+    #[serde(rename = "startAt", skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchResponse {
+    #[serde(default)]
+    pub issues: Vec<JiraIssue>,
+    #[serde(rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+    pub total: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JiraIssue {
+    pub key: String,
+    #[serde(default)]
+    pub fields: IssueFields,
+}
+
+impl JiraIssue {
+    pub fn is_resolved(&self) -> bool {
+        let done = self
+            .fields
+            .status
+            .as_ref()
+            .and_then(|status| status.status_category.as_ref())
+            .is_some_and(|category| category.key == "done");
+        self.fields.resolution.is_some() || done
+    }
+
+    pub fn status_name(&self) -> String {
+        match &self.fields.status {
+            Some(status) => status.name.to_string(),
+            None => "Resolved".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct IssueFields {
+    pub summary: Option<String>,
+    pub status: Option<IssueStatus>,
+    pub resolution: Option<Resolution>,
+    #[serde(rename = "issuetype")]
+    pub issue_type: Option<IssueType>,
+    pub parent: Option<ParentIssue>,
+    // Custom fields, like the Epic Link on Server and Data Center
+    #[serde(flatten)]
+    pub custom: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueType {
+    pub name: String,
+    #[serde(rename = "hierarchyLevel")]
+    pub hierarchy_level: Option<i32>,
+}
+
+impl IssueType {
+    // Epics sit one level above stories, older sites only name them
+    pub fn is_epic(&self) -> bool {
+        self.hierarchy_level == Some(1) || self.name.eq_ignore_ascii_case("epic")
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ParentIssue {
+    pub key: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JiraField {
+    pub id: String,
+    pub name: String,
+    pub schema: Option<FieldSchema>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FieldSchema {
+    pub custom: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueStatus {
+    pub name: String,
+    #[serde(rename = "statusCategory")]
+    pub status_category: Option<StatusCategory>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StatusCategory {
+    pub key: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Resolution {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JiraUser {
+    // Server has user keys instead of account ids
+    #[serde(rename = "accountId", alias = "key")]
+    pub account_id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(rename = "emailAddress")]
+    pub email_address: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Permission {
+    #[serde(rename = "havePermission")]
+    pub have_permission: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JiraProject {
+    pub key: String,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ProjectPage {
+    #[serde(default)]
+    pub values: Vec<JiraProject>,
+    #[serde(rename = "isLast", default = "default_is_last")]
+    pub is_last: bool,
+}
+
+fn default_is_last() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PermissionsResponse {
+    pub permissions: HashMap<String, Permission>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommentText {
+    #[serde(rename = "type")]
+    pub node_type: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommentParagraph {
+    #[serde(rename = "type")]
+    pub node_type: String,
+    pub content: Vec<CommentText>,
+}
+
+// Jira Cloud comments are Atlassian documents, a single paragraph is enough here
+#[derive(Serialize, Debug)]
+pub struct Comment {
+    #[serde(rename = "type")]
+    pub node_type: String,
+    pub version: u32,
+    pub content: Vec<CommentParagraph>,
+}
+
+impl Comment {
+    pub fn from_text(text: &str) -> Comment {
+        Comment {
+            node_type: "doc".to_string(),
+            version: 1,
+            content: vec![CommentParagraph {
+                node_type: "paragraph".to_string(),
+                content: vec![CommentText {
+                    node_type: "text".to_string(),
+                    text: text.to_string(),
+                }],
+            }],
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct NewWorklog {
+    pub started: String,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<WorklogComment>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum WorklogComment {
+    Document(Comment),
+    Text(String),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Transition {
+    pub id: String,
+    pub name: String,
+    pub to: IssueStatus,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TransitionsResponse {
+    pub transitions: Vec<Transition>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TransitionId {
+    pub id: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TransitionRequest {
+    pub transition: TransitionId,
+}
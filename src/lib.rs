@@ -0,0 +1,17 @@
+#![feature(let_chains)]
+
+pub mod approve;
+pub mod jira;
+pub mod prompts;
+pub mod pull;
+pub mod report;
+pub mod serve;
+pub mod sink;
+pub mod status;
+pub mod storage;
+pub mod sync;
+pub mod tempo;
+pub mod timer;
+pub mod toggl;
+pub mod transition;
+pub mod utils;
@@ -1,20 +1,20 @@
-#![feature(let_chains)]
-
-use crate::{
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use toggl_sync::{
+    approve::{approval_status, approval_submit},
+    pull::pull_worklogs,
+    report::{show_report, ReportGrouping},
+    serve::serve,
+    status::show_status,
     storage::{
+        config::configure,
         credentials::clear_credentials,
-        keys::{add_key, filter_keys},
+        keys::{add_key, filter_keys, import_keys},
+        use_profile,
     },
     sync::sync_toggle,
+    timer::{start_timer, stop_timer},
 };
-use clap::{Parser, Subcommand};
-use colored::Colorize;
-
-mod storage;
-mod sync;
-mod tempo;
-mod toggl;
-mod utils;
 
 const EFFECTSOFT_ASCII: &str = r"  ______ ______ ______ ______ _____ _______ _____  ____  ______ _______ 
 |  ____|  ____|  ____|  ____/ ____|__   __/ ____|/ __ \|  ____|__   __|
@@ -24,6 +24,22 @@ const EFFECTSOFT_ASCII: &str = r"  ______ ______ ______ ______ _____ _______ ___
 |______|_|    |_|    |______\_____|  |_| |_____/ \____/|_|       |_|
 ";
 
+#[derive(Subcommand, Debug)]
+enum ApproveCommand {
+    /// - Show the approval status of a week
+    Status {
+        /// Use the current week instead of asking
+        #[clap(short, long, default_value_t = false)]
+        week: bool,
+    },
+    /// - Submit a week's timesheet for approval
+    Submit {
+        /// Use the current week instead of asking
+        #[clap(short, long, default_value_t = false)]
+        week: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum Command {
     /// - Run through the whole sync process
@@ -32,8 +48,51 @@ enum Command {
     AddKeys,
     /// - Go through the list of available keys and remove old ones
     FilterKeys,
+    /// - Import keys from Jira into the key history
+    ImportKeys {
+        /// Offer to remove keys that no longer match the query
+        #[clap(short, long, default_value_t = false)]
+        prune: bool,
+    },
     /// - Remove the credentials files
     ClearCredentials,
+    /// - Change the sync settings
+    Configure,
+    /// - Create Toggl entries for Tempo worklogs missing in Toggl
+    Pull,
+    /// - Show logged time compared to your Tempo schedule
+    Status {
+        /// Use the current week instead of asking
+        #[clap(short, long, default_value_t = false)]
+        week: bool,
+    },
+    /// - Start a Toggl timer for an issue
+    Start,
+    /// - Stop the running Toggl timer
+    Stop,
+    /// - Summarize tracked time per issue next to what was synced
+    Report {
+        /// Use the current week instead of asking
+        #[clap(short, long, default_value_t = false, conflicts_with = "month")]
+        week: bool,
+        /// Use the current month instead of asking
+        #[clap(short, long, default_value_t = false)]
+        month: bool,
+        /// Total the synced worklogs per issue or per Jira epic
+        #[clap(long, value_enum, default_value_t = ReportGrouping::Issue)]
+        by: ReportGrouping,
+    },
+    /// - Receive Toggl webhooks and sync stopped entries right away
+    Serve {
+        /// Local port to listen on
+        #[clap(short, long, default_value_t = 8787)]
+        port: u16,
+    },
+    /// - Handle the Tempo timesheet approval
+    Approve {
+        #[clap(subcommand)]
+        cmd: ApproveCommand,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -41,6 +100,9 @@ enum Command {
 struct Args {
     #[clap(subcommand)]
     cmd: Command,
+    /// Keep the credentials, settings and history of this profile apart
+    #[clap(short = 'P', long, global = true)]
+    profile: Option<String>,
     #[clap(short, long, default_value_t = false)]
     fast: bool,
 }
@@ -49,10 +111,23 @@ struct Args {
 async fn main() -> anyhow::Result<()> {
     println!("{}", EFFECTSOFT_ASCII.red());
     let arguments = Args::parse();
+    use_profile(arguments.profile);
     match arguments.cmd {
         Command::Sync => sync_toggle().await,
-        Command::AddKeys => add_key(),
+        Command::AddKeys => add_key().await,
         Command::FilterKeys => filter_keys(),
+        Command::ImportKeys { prune } => import_keys(prune).await,
         Command::ClearCredentials => clear_credentials(),
+        Command::Configure => configure().await,
+        Command::Pull => pull_worklogs().await,
+        Command::Status { week } => show_status(week).await,
+        Command::Start => start_timer().await,
+        Command::Stop => stop_timer().await,
+        Command::Report { week, month, by } => show_report(week, month, by).await,
+        Command::Serve { port } => serve(port).await,
+        Command::Approve { cmd } => match cmd {
+            ApproveCommand::Status { week } => approval_status(week).await,
+            ApproveCommand::Submit { week } => approval_submit(week).await,
+        },
     }
 }
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use inquire::{Confirm, DateSelect, Select, Text};
+
+#[cfg(any(test, feature = "test-prompts"))]
+pub use scripted::{remaining_answers, script_answers};
+#[cfg(any(test, feature = "test-prompts"))]
+use scripted::next_answer;
+
+// Only the tests can script the prompts, a normal build always asks the terminal
+#[cfg(any(test, feature = "test-prompts"))]
+mod scripted {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref SCRIPTED_ANSWERS: Mutex<Option<VecDeque<String>>> = Mutex::new(None);
+    }
+
+    // Answers the next prompts from the list instead of the terminal, an empty answer picks the default
+    pub fn script_answers(answers: &[&str]) {
+        let mut scripted = SCRIPTED_ANSWERS.lock().unwrap();
+        *scripted = Some(answers.iter().map(|answer| answer.to_string()).collect());
+    }
+
+    pub fn remaining_answers() -> Vec<String> {
+        let scripted = SCRIPTED_ANSWERS.lock().unwrap();
+        match scripted.as_ref() {
+            Some(answers) => answers.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub(super) fn next_answer(message: &str) -> Option<String> {
+        let mut scripted = SCRIPTED_ANSWERS.lock().unwrap();
+        let answer = scripted.as_mut()?.pop_front();
+        if answer.is_none() {
+            println!("No scripted answer left for: {}", message);
+        }
+        answer
+    }
+}
+
+#[cfg(not(any(test, feature = "test-prompts")))]
+fn next_answer(_message: &str) -> Option<String> {
+    None
+}
+
+pub fn text(prompt: Text) -> anyhow::Result<String> {
+    match next_answer(prompt.message) {
+        Some(answer) if answer.is_empty() => Ok(prompt.default.unwrap_or_default().to_string()),
+        Some(answer) => Ok(answer),
+        None => Ok(prompt.prompt()?),
+    }
+}
+
+// Escape skips the prompt, a scripted empty answer without a default does the same
+pub fn text_skippable(prompt: Text) -> anyhow::Result<Option<String>> {
+    match next_answer(prompt.message) {
+        Some(answer) if answer.is_empty() => Ok(prompt.default.map(|default| default.to_string())),
+        Some(answer) => Ok(Some(answer)),
+        None => Ok(prompt.prompt_skippable()?),
+    }
+}
+
+pub fn confirm(prompt: Confirm) -> anyhow::Result<bool> {
+    match next_answer(prompt.message) {
+        Some(answer) if answer.is_empty() => Ok(prompt.default.unwrap_or_default()),
+        Some(answer) => Ok(answer.to_lowercase().starts_with('y')),
+        None => Ok(prompt.prompt()?),
+    }
+}
+
+pub fn date(prompt: DateSelect) -> anyhow::Result<NaiveDate> {
+    match next_answer(prompt.message) {
+        Some(answer) if answer.is_empty() => Ok(prompt.starting_date),
+        Some(answer) => Ok(NaiveDate::parse_from_str(&answer, "%Y-%m-%d")?),
+        None => Ok(prompt.prompt()?),
+    }
+}
+
+pub fn select<T: Display>(prompt: Select<T>) -> anyhow::Result<T> {
+    match next_answer(prompt.message) {
+        Some(answer) => {
+            let Select {
+                mut options,
+                starting_cursor,
+                ..
+            } = prompt;
+            let index = if answer.is_empty() {
+                starting_cursor
+            } else {
+                options
+                    .iter()
+                    .position(|option| option.to_string() == answer)
+                    .ok_or_else(|| anyhow::anyhow!("{} is not one of the options", answer))?
+            };
+            Ok(options.swap_remove(index))
+        }
+        None => Ok(prompt.prompt()?),
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc, Weekday};
+use colored::Colorize;
+use humantime::format_duration;
+use inquire::{Confirm, DateSelect, Select};
+use reqwest::Client;
+
+use crate::{
+    prompts,
+    storage::{
+        config::{retrieve_config, Config},
+        credentials::{retrieve_credentials, Credentials},
+    },
+    tempo::{service::get_worklogs, structs::WorkLogResult},
+    toggl::{
+        service::{create_entry, retrieve_entries, retrieve_workspaces},
+        structs::{NewTimeEntry, TimeEntry},
+    },
+    utils::KeyMatcher,
+};
+
+pub async fn pull_worklogs() -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    run_pull(&credentials, &config).await
+}
+
+pub async fn run_pull(credentials: &Credentials, config: &Config) -> anyhow::Result<()> {
+    let from = prompts::date(
+        DateSelect::new("Pull Tempo worklogs from which day?")
+            .with_starting_date(Utc::now().date_naive())
+            .with_week_start(Weekday::Mon),
+    )?;
+    let to = prompts::date(
+        DateSelect::new("Until which day?")
+            .with_starting_date(from)
+            .with_min_date(from)
+            .with_week_start(Weekday::Mon),
+    )?;
+    let client = Client::new();
+    let (toggl_username, toggl_password) = credentials.toggl_basic_auth();
+    let worklogs = get_worklogs(
+        &client,
+        config.tempo_url(),
+        config.jira_flavour(),
+        credentials.tempo_auth(config.jira_flavour()),
+        &credentials.account_id,
+        &from.format("%Y-%m-%d").to_string(),
+        &to.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    let entries = retrieve_entries(
+        &client,
+        config.toggl_url(),
+        toggl_username,
+        toggl_password,
+        from,
+        to,
+    )
+    .await?
+    .into_iter()
+    .filter(|entry| entry.server_deleted_at.is_none() && config.allows_workspace(entry.workspace_id))
+    .collect::<Vec<_>>();
+
+    let worklog_count = worklogs.len();
+    let key_matcher = config.key_matcher()?;
+    let missing = worklogs
+        .into_iter()
+        .filter(|worklog| !is_covered(worklog, &entries, &key_matcher))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        println!(
+            "Toggl already covers all {} Tempo worklogs",
+            worklog_count.to_string().blue()
+        );
+        return Ok(());
+    }
+    println!(
+        "Found {} Tempo worklogs missing in Toggl:",
+        missing.len().to_string().red()
+    );
+    for worklog in missing.iter() {
+        println!(
+            "{} {} {}: {} {}",
+            worklog.start_date,
+            worklog.start_time,
+            worklog.issue.key.blue(),
+            worklog.description,
+            format_duration(Duration::from_secs(worklog.time_spent_seconds))
+        );
+    }
+    if !prompts::confirm(Confirm::new(&format!(
+        "Create {} Toggl entries? (y/n)",
+        missing.len()
+    )))? {
+        return Ok(());
+    }
+
+    let workspace_id = select_entry_workspace(&client, config, toggl_username, toggl_password).await?;
+    for worklog in missing {
+        let Some(start) = worklog_start(&worklog) else {
+            println!("{} has an invalid start, skipping!", worklog.issue.key);
+            continue;
+        };
+        let entry = NewTimeEntry {
+            created_with: "toggl_sync".to_string(),
+            description: format!("{} {}", worklog.issue.key, worklog.description),
+            tags: vec![worklog.issue.key.to_string()],
+            start: start.to_rfc3339(),
+            duration: worklog.time_spent_seconds as i64,
+            workspace_id,
+            project_id: None,
+        };
+        match create_entry(&client, config.toggl_url(), toggl_username, toggl_password, &entry).await {
+            Ok(_) => println!("{} was added to toggl!", worklog.issue.key),
+            Err(_) => println!("{} failed to be added to toggl!", worklog.issue.key),
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn select_entry_workspace(
+    client: &Client,
+    config: &Config,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<i64> {
+    if let Some(workspace_ids) = &config.workspace_ids && workspace_ids.len() == 1 {
+        return Ok(workspace_ids[0]);
+    }
+    let workspaces = retrieve_workspaces(client, config.toggl_url(), username, password)
+        .await?
+        .into_iter()
+        .filter(|workspace| config.allows_workspace(workspace.id))
+        .collect::<Vec<_>>();
+    if workspaces.len() == 1 {
+        return Ok(workspaces[0].id);
+    }
+    let options = workspaces
+        .iter()
+        .map(|workspace| format!("{} ({})", workspace.name, workspace.id))
+        .collect::<Vec<String>>();
+    let selected = prompts::select(Select::new("Which Toggl workspace?", options.clone()))?;
+    let index = options.iter().position(|x| *x == selected).unwrap_or(0);
+    Ok(workspaces[index].id)
+}
+
+fn worklog_start(worklog: &WorkLogResult) -> Option<DateTime<Utc>> {
+    let start = NaiveDateTime::parse_from_str(
+        &format!("{} {}", worklog.start_date, worklog.start_time),
+        "%Y-%m-%d %H:%M:%S",
+    )
+    .ok()?;
+    Some(Utc.from_utc_datetime(&start))
+}
+
+fn entry_key(entry: &TimeEntry, key_matcher: &KeyMatcher) -> Option<String> {
+    let tag_key = entry
+        .tags
+        .iter()
+        .flatten()
+        .find_map(|tag| key_matcher.find(tag));
+    tag_key.or_else(|| key_matcher.find(&entry.description))
+}
+
+// Covered when a Toggl entry with the same key overlaps the worklog
+fn is_covered(worklog: &WorkLogResult, entries: &[TimeEntry], key_matcher: &KeyMatcher) -> bool {
+    let Some(start) = worklog_start(worklog) else {
+        return false;
+    };
+    let end = start + chrono::Duration::seconds(worklog.time_spent_seconds as i64);
+    entries.iter().any(|entry| {
+        let Some(entry_start) = entry.start.map(|start| start.with_timezone(&Utc)) else {
+            return false;
+        };
+        let entry_end = if entry.duration.is_positive() {
+            entry_start + chrono::Duration::seconds(entry.duration)
+        } else {
+            Utc::now()
+        };
+        entry_key(entry, key_matcher).as_deref() == Some(worklog.issue.key.as_str())
+            && entry_start < end
+            && start < entry_end
+    })
+}
@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{NaiveDate, Utc, Weekday};
+use colored::Colorize;
+use inquire::{DateSelect, Select};
+use reqwest::Client;
+
+use crate::{
+    jira::service::{find_issues, get_epic_link_field, JiraSite},
+    storage::{
+        config::{retrieve_config, Config, JiraFlavour},
+        credentials::{retrieve_credentials, Credentials},
+        jira::{retreive_issue_parents, store_issue_parents, IssueParent},
+    },
+    tempo::{service::get_worklogs, structs::WorkLogResult},
+    toggl::{
+        service::{cached_metadata, retrieve_summary, retrieve_workspaces},
+        structs::SummaryGroup,
+    },
+    utils::{clean_description, format_hours, month_bounds, week_bounds, KeyMatcher},
+};
+
+const PARENT_CACHE_SECONDS: i64 = 7 * 24 * 60 * 60;
+// Sub-task, story, epic
+const MAX_PARENT_DEPTH: usize = 3;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ReportGrouping {
+    Issue,
+    Epic,
+}
+
+pub async fn show_report(week: bool, month: bool, by: ReportGrouping) -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    let (from, to) = select_period(week, month)?;
+    match by {
+        ReportGrouping::Issue => run_report(&credentials, &config, from, to).await,
+        ReportGrouping::Epic => run_epic_report(&credentials, &config, from, to).await,
+    }
+}
+
+fn select_period(week: bool, month: bool) -> anyhow::Result<(NaiveDate, NaiveDate)> {
+    let today = Utc::now().date_naive();
+    if month {
+        return Ok(month_bounds(today));
+    }
+    if week {
+        return Ok(week_bounds(today));
+    }
+    let period = Select::new("Report on a week or a month?", vec!["Week", "Month"]).prompt()?;
+    let selected_date = DateSelect::new(&format!("Which {}?", period.to_lowercase()))
+        .with_starting_date(today)
+        .with_week_start(Weekday::Mon)
+        .prompt()?;
+    match period {
+        "Month" => Ok(month_bounds(selected_date)),
+        _ => Ok(week_bounds(selected_date)),
+    }
+}
+
+pub async fn run_report(
+    credentials: &Credentials,
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> anyhow::Result<()> {
+    let client = Client::new();
+    let (toggl_username, toggl_password) = credentials.toggl_basic_auth();
+    let workspace_ids = match &config.workspace_ids {
+        Some(workspace_ids) => workspace_ids.clone(),
+        None => retrieve_workspaces(&client, config.toggl_url(), toggl_username, toggl_password)
+            .await?
+            .into_iter()
+            .map(|workspace| workspace.id)
+            .collect(),
+    };
+    let mut groups = Vec::new();
+    for workspace_id in workspace_ids {
+        let summary = retrieve_summary(
+            &client,
+            config.reports_url(),
+            toggl_username,
+            toggl_password,
+            workspace_id,
+            from,
+            to,
+        )
+        .await?;
+        groups.extend(summary);
+    }
+    let worklogs = get_worklogs(
+        &client,
+        config.tempo_url(),
+        config.jira_flavour(),
+        credentials.tempo_auth(config.jira_flavour()),
+        &credentials.account_id,
+        &from.format("%Y-%m-%d").to_string(),
+        &to.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    let metadata = cached_metadata(
+        &client,
+        config.toggl_url(),
+        toggl_username,
+        toggl_password,
+        config.metadata_refresh_hours(),
+    )
+    .await;
+
+    println!("Tracked in Toggl {} - {}:", from, to);
+    for group in groups.iter() {
+        let total = group.sub_groups.iter().map(|sub_group| sub_group.seconds).sum::<u64>();
+        let project = metadata
+            .project_label(group.id)
+            .unwrap_or_else(|| "No project".to_string());
+        println!("{} {}", project.bold(), format_hours(total).blue());
+        for sub_group in group.sub_groups.iter() {
+            println!(
+                "  {} {}",
+                format_hours(sub_group.seconds),
+                sub_group.title.as_deref().unwrap_or("(no description)")
+            );
+        }
+    }
+
+    let totals = issue_totals(&groups, &worklogs, &config.key_matcher()?);
+    println!("Per issue, tracked compared to synced:");
+    let keys = totals
+        .tracked
+        .keys()
+        .chain(totals.synced.keys())
+        .collect::<BTreeSet<&String>>();
+    for key in keys {
+        let tracked_seconds = totals.tracked.get(key).copied().unwrap_or(0);
+        let synced_seconds = totals.synced.get(key).copied().unwrap_or(0);
+        // Tempo only keeps whole minutes
+        let synced_hours = if synced_seconds + 60 <= tracked_seconds {
+            format_hours(synced_seconds).red()
+        } else {
+            format_hours(synced_seconds).green()
+        };
+        println!(
+            "{} {} tracked, {} synced",
+            key.blue(),
+            format_hours(tracked_seconds),
+            synced_hours
+        );
+    }
+    if totals.without_key > 0 {
+        println!("{} tracked without a key", format_hours(totals.without_key).red());
+    }
+    for (description, seconds) in totals.ambiguous.iter() {
+        println!(
+            "{} tracked on {}, synced to several issues",
+            format_hours(*seconds).yellow(),
+            description
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct IssueTotals {
+    pub tracked: BTreeMap<String, u64>,
+    pub synced: BTreeMap<String, u64>,
+    pub without_key: u64,
+    // Descriptions synced to more than one issue
+    pub ambiguous: BTreeMap<String, u64>,
+}
+
+pub fn issue_totals(
+    groups: &[SummaryGroup],
+    worklogs: &[WorkLogResult],
+    key_matcher: &KeyMatcher,
+) -> IssueTotals {
+    // Synced entries keep their key in the tags only, match those through the Tempo descriptions
+    let mut synced_descriptions: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for worklog in worklogs.iter() {
+        synced_descriptions
+            .entry(clean_description(&worklog.description))
+            .or_default()
+            .insert(worklog.issue.key.to_string());
+    }
+    let mut totals = IssueTotals::default();
+    for sub_group in groups.iter().flat_map(|group| group.sub_groups.iter()) {
+        let title = sub_group.title.as_deref().unwrap_or_default();
+        if let Some(key) = key_matcher.find(title) {
+            *totals.tracked.entry(key).or_default() += sub_group.seconds;
+            continue;
+        }
+        let description = clean_description(title);
+        match synced_descriptions.get(&description) {
+            Some(keys) if keys.len() == 1 => {
+                *totals.tracked.entry(keys.first().unwrap().to_string()).or_default() += sub_group.seconds
+            }
+            // Synced to several issues, no way to tell which one this time belongs to
+            Some(_) => *totals.ambiguous.entry(description).or_default() += sub_group.seconds,
+            None => totals.without_key += sub_group.seconds,
+        }
+    }
+    for worklog in worklogs.iter() {
+        *totals.synced.entry(worklog.issue.key.to_string()).or_default() +=
+            worklog.time_spent_seconds;
+    }
+    totals
+}
+
+pub async fn run_epic_report(
+    credentials: &Credentials,
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> anyhow::Result<()> {
+    let Some(site) = config.jira_site(credentials) else {
+        println!("{}", "Set the Jira site url with configure first".red());
+        return Ok(());
+    };
+    let client = Client::new();
+    let worklogs = get_worklogs(
+        &client,
+        config.tempo_url(),
+        config.jira_flavour(),
+        credentials.tempo_auth(config.jira_flavour()),
+        &credentials.account_id,
+        &from.format("%Y-%m-%d").to_string(),
+        &to.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    let mut parents = retreive_issue_parents()?;
+    let keys = worklogs
+        .iter()
+        .map(|worklog| worklog.issue.key.to_string())
+        .collect::<BTreeSet<String>>();
+    resolve_parents(&client, &site, &mut parents, keys).await?;
+    store_issue_parents(&parents)?;
+
+    let summary_of = |key: &str| {
+        parents
+            .get(key)
+            .map(|parent| parent.summary.to_string())
+            .unwrap_or_default()
+    };
+    println!("Synced per epic {} - {}:", from, to);
+    for total in epic_totals(&worklogs, &parents) {
+        match &total.epic {
+            Some(epic) => println!(
+                "{} {} {}",
+                epic.blue(),
+                summary_of(epic).bold(),
+                format_hours(total.seconds).blue()
+            ),
+            None => println!("{} {}", "No epic".bold(), format_hours(total.seconds).blue()),
+        }
+        for (key, seconds) in total.issues {
+            println!("  {} {} {}", format_hours(seconds), key, summary_of(&key).dimmed());
+        }
+    }
+    Ok(())
+}
+
+pub struct EpicTotal {
+    pub epic: Option<String>,
+    pub seconds: u64,
+    pub issues: BTreeMap<String, u64>,
+}
+
+// Largest epics first, time without an epic last
+pub fn epic_totals(
+    worklogs: &[WorkLogResult],
+    parents: &HashMap<String, IssueParent>,
+) -> Vec<EpicTotal> {
+    let mut per_epic: HashMap<Option<String>, BTreeMap<String, u64>> = HashMap::new();
+    for worklog in worklogs.iter() {
+        let key = &worklog.issue.key;
+        *per_epic
+            .entry(epic_of(key, parents))
+            .or_default()
+            .entry(key.to_string())
+            .or_default() += worklog.time_spent_seconds;
+    }
+    let mut totals = per_epic
+        .into_iter()
+        .map(|(epic, issues)| EpicTotal {
+            epic,
+            seconds: issues.values().sum(),
+            issues,
+        })
+        .collect::<Vec<_>>();
+    totals.sort_by_key(|total| (total.epic.is_none(), std::cmp::Reverse(total.seconds)));
+    totals
+}
+
+// Looks up the issues and their parents up to the epics, lookups are cached for a while
+async fn resolve_parents(
+    client: &Client,
+    site: &JiraSite,
+    parents: &mut HashMap<String, IssueParent>,
+    keys: BTreeSet<String>,
+) -> anyhow::Result<()> {
+    let now = Utc::now().timestamp();
+    let epic_link = match site.flavour {
+        JiraFlavour::Cloud => None,
+        JiraFlavour::Server => {
+            let field = get_epic_link_field(client, site).await?;
+            if field.is_none() {
+                println!("{}", "No Epic Link field in Jira, only parents are followed".yellow());
+            }
+            field
+        }
+    };
+    let mut fields = vec!["summary", "issuetype", "parent"];
+    fields.extend(epic_link.as_deref());
+    let mut level = keys;
+    for _ in 0..MAX_PARENT_DEPTH {
+        let stale = level
+            .iter()
+            .filter(|key| {
+                parents
+                    .get(*key)
+                    .is_none_or(|parent| now - parent.checked_at >= PARENT_CACHE_SECONDS)
+            })
+            .cloned()
+            .collect::<Vec<String>>();
+        if !stale.is_empty() {
+            let issues = find_issues(client, site, &stale, &fields).await?;
+            // Issues missing in Jira are cached without a parent
+            for key in stale {
+                parents.insert(
+                    key,
+                    IssueParent {
+                        summary: String::new(),
+                        parent: None,
+                        is_epic: false,
+                        checked_at: now,
+                    },
+                );
+            }
+            for issue in issues {
+                let epic = epic_link
+                    .as_ref()
+                    .and_then(|field| issue.fields.custom.get(field))
+                    .and_then(|value| value.as_str())
+                    .map(|key| key.to_string());
+                parents.insert(
+                    issue.key.to_string(),
+                    IssueParent {
+                        summary: issue.fields.summary.unwrap_or_default(),
+                        parent: issue.fields.parent.map(|parent| parent.key).or(epic),
+                        is_epic: issue.fields.issue_type.is_some_and(|issue_type| issue_type.is_epic()),
+                        checked_at: now,
+                    },
+                );
+            }
+        }
+        level = level
+            .iter()
+            .filter_map(|key| parents.get(key))
+            .filter(|parent| !parent.is_epic)
+            .filter_map(|parent| parent.parent.clone())
+            .collect();
+        if level.is_empty() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+pub fn epic_of(key: &str, parents: &HashMap<String, IssueParent>) -> Option<String> {
+    let mut current = key;
+    for _ in 0..MAX_PARENT_DEPTH {
+        let parent = parents.get(current)?;
+        if parent.is_epic {
+            return Some(current.to_string());
+        }
+        current = parent.parent.as_deref()?;
+    }
+    None
+}
@@ -0,0 +1,245 @@
+use std::{convert::Infallible, net::TcpListener, sync::Arc};
+
+use colored::Colorize;
+use hmac::{Hmac, Mac};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use inquire::Password;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+use crate::{
+    storage::{
+        config::{retrieve_config, store_config, Config},
+        credentials::{retrieve_credentials, Credentials},
+        keys::{retreive_keys, store_keys},
+        webhooks::{retreive_queue, store_queue, QueuedEntry},
+    },
+    sink::create_worklogs,
+    sync::{build_worklog, canonical_description, resolve_key_desc},
+    toggl::{
+        service::{describe_entries, merge_filter_entries, tag_entries},
+        structs::TimeEntry,
+    },
+    transition::{print_transitioned, transition_logged_issues},
+    utils::{clean_key, KeyMatcher},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIGNATURE_HEADER: &str = "x-webhook-signature-256";
+
+#[derive(Deserialize, Debug)]
+struct WebhookMetadata {
+    action: String,
+    model: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct WebhookEvent {
+    validation_code: Option<String>,
+    metadata: Option<WebhookMetadata>,
+    payload: Option<serde_json::Value>,
+}
+
+struct ServeState {
+    credentials: Credentials,
+    config: Config,
+    key_matcher: KeyMatcher,
+    client: Client,
+    // Webhooks can arrive concurrently, the queue file is read and written as a whole
+    queue_lock: Mutex<()>,
+}
+
+pub async fn serve(port: u16) -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let mut config = retrieve_config()?;
+    if config.webhook_secret.is_none() {
+        let secret = Password::new("Toggl webhook secret")
+            .with_help_message("The secret set on the webhook subscription in Toggl")
+            .prompt()?;
+        config.webhook_secret = Some(secret);
+        store_config(&config)?;
+    }
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!(
+        "Listening for Toggl webhooks on {}",
+        format!("http://127.0.0.1:{}", port).blue()
+    );
+    run_serve(credentials, config, listener).await
+}
+
+pub async fn run_serve(
+    credentials: Credentials,
+    config: Config,
+    listener: TcpListener,
+) -> anyhow::Result<()> {
+    let state = Arc::new(ServeState {
+        credentials,
+        key_matcher: config.key_matcher()?,
+        config,
+        client: Client::new(),
+        queue_lock: Mutex::new(()),
+    });
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| handle_request(state.clone(), request)))
+        }
+    });
+    listener.set_nonblocking(true)?;
+    Server::from_tcp(listener)?.serve(make_service).await?;
+    Ok(())
+}
+
+async fn handle_request(
+    state: Arc<ServeState>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::POST {
+        return Ok(respond(StatusCode::METHOD_NOT_ALLOWED, json!({})));
+    }
+    let signature = request
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let Ok(body) = hyper::body::to_bytes(request.into_body()).await else {
+        return Ok(respond(StatusCode::BAD_REQUEST, json!({})));
+    };
+    let secret = state.config.webhook_secret.as_deref().unwrap_or_default();
+    if !verify_signature(secret, &body, signature.as_deref()) {
+        println!("{}", "Rejected a webhook with an invalid signature".red());
+        return Ok(respond(StatusCode::UNAUTHORIZED, json!({})));
+    }
+    let Ok(event) = serde_json::from_slice::<WebhookEvent>(&body) else {
+        return Ok(respond(StatusCode::BAD_REQUEST, json!({})));
+    };
+    if let Some(validation_code) = event.validation_code {
+        println!("Validated the Toggl webhook subscription");
+        return Ok(respond(
+            StatusCode::OK,
+            json!({ "validation_code": validation_code }),
+        ));
+    }
+    if let Some(metadata) = event.metadata
+        && metadata.model == "time_entry"
+        && (metadata.action == "created" || metadata.action == "updated")
+        && let Some(payload) = event.payload
+        && let Ok(entry) = serde_json::from_value::<TimeEntry>(payload)
+        && let Err(error) = handle_entry(&state, entry).await
+    {
+        println!("{} {}", "Failed to handle the webhook:".red(), error);
+    }
+    Ok(respond(StatusCode::OK, json!({})))
+}
+
+// Stopped entries with a resolvable key are posted right away, the rest wait for the next sync
+async fn handle_entry(state: &ServeState, entry: TimeEntry) -> anyhow::Result<()> {
+    let _guard = state.queue_lock.lock().await;
+    let mut queue = retreive_queue()?;
+    queue.pending.retain(|queued| queued.id != entry.id);
+    // Updates to entries already in Tempo are left alone
+    if queue.is_synced(entry.id) || !state.config.allows_workspace(entry.workspace_id) {
+        return store_queue(&queue);
+    }
+    // Running and deleted entries are filtered out here
+    let Some(merged) = merge_filter_entries(vec![entry.clone()]).pop() else {
+        return store_queue(&queue);
+    };
+    let (key, desc) = resolve_key_desc(&merged, &state.key_matcher);
+    let Some(key) = key.map(|key| clean_key(&key)) else {
+        println!("Queued {} for the next sync, no key found", entry.description.blue());
+        queue.pending.push(QueuedEntry::from_entry(&entry));
+        return store_queue(&queue);
+    };
+
+    let canonical = canonical_description(&key, &desc);
+    let worklog = build_worklog(&state.credentials, &state.config, &merged, &key, desc.to_string());
+    let failed = create_worklogs(&state.credentials, &state.config, vec![worklog]).await?;
+    if !failed.is_empty() {
+        println!("{} was queued for the next sync!", key.red());
+        queue.pending.push(QueuedEntry::from_entry(&entry));
+        return store_queue(&queue);
+    }
+    queue.mark_synced(&entry);
+    store_queue(&queue)?;
+    if let Some(site) = state.config.jira_site(&state.credentials) {
+        let transitioned =
+            transition_logged_issues(&state.client, &site, &state.config, &[key.to_string()]).await;
+        print_transitioned(&transitioned);
+    }
+
+    let mut available_keys = retreive_keys()?;
+    if !available_keys.contains_key(&key) {
+        available_keys.insert(key.to_string(), desc);
+        store_keys(available_keys)?;
+    }
+    let (username, password) = state.credentials.toggl_basic_auth();
+    if !entry.tags.iter().flatten().any(|tag| *tag == key) {
+        let tagged = tag_entries(
+            &state.client,
+            state.config.toggl_url(),
+            username,
+            password,
+            entry.workspace_id,
+            &[entry.id],
+            &key,
+        )
+        .await;
+        if tagged.is_err() {
+            println!("{} {} with {}", "Failed to tag Toggl entry".red(), entry.id, key);
+        }
+    }
+    if state.config.rewrite_descriptions() && entry.description != canonical {
+        let renamed = describe_entries(
+            &state.client,
+            state.config.toggl_url(),
+            username,
+            password,
+            entry.workspace_id,
+            &[entry.id],
+            &canonical,
+        )
+        .await;
+        if renamed.is_err() {
+            println!("{} {} to {}", "Failed to rename Toggl entry".red(), entry.id, canonical);
+        }
+    }
+    Ok(())
+}
+
+fn verify_signature(secret: &str, body: &[u8], header: Option<&str>) -> bool {
+    let Some(signature) = header
+        .and_then(|header| header.strip_prefix("sha256="))
+        .and_then(decode_hex)
+    else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn respond(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response
+}
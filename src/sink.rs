@@ -0,0 +1,126 @@
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{header::RETRY_AFTER, Client, Response, StatusCode};
+use tokio::{sync::Semaphore, task::JoinSet, time::sleep};
+
+use crate::{
+    jira,
+    storage::{
+        config::{Config, WorklogSink},
+        credentials::Credentials,
+    },
+    tempo::{self, structs::Worklog},
+};
+
+const MAX_CONCURRENT_POSTS: usize = 4;
+const MAX_RETRIES: u32 = 5;
+
+impl WorklogSink {
+    async fn post(
+        &self,
+        client: &Client,
+        credentials: &Credentials,
+        config: &Config,
+        worklog: &Worklog,
+    ) -> anyhow::Result<Response> {
+        match self {
+            WorklogSink::Tempo => {
+                tempo::service::create_worklog(
+                    client,
+                    config.tempo_url(),
+                    config.jira_flavour(),
+                    credentials.tempo_auth(config.jira_flavour()).to_string(),
+                    worklog,
+                )
+                .await
+            }
+            WorklogSink::Jira => {
+                let Some(site) = config.jira_site(credentials) else {
+                    anyhow::bail!("No Jira site or credentials are configured");
+                };
+                jira::service::create_worklog(client, &site, worklog).await
+            }
+        }
+    }
+}
+
+// Posts every worklog to the sink of its key and returns the ones that failed
+pub async fn create_worklogs(
+    credentials: &Credentials,
+    config: &Config,
+    worklogs: Vec<Worklog>,
+) -> anyhow::Result<Vec<Worklog>> {
+    let client = Client::new();
+    let context = Arc::new((credentials.clone(), config.clone()));
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_POSTS));
+    let mut tasks = JoinSet::new();
+    for (index, log) in worklogs.into_iter().enumerate() {
+        let client = client.clone();
+        let context = context.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let (credentials, config) = context.as_ref();
+            let sink = config.sink_for(&log.issue_key);
+            let response = post_with_retry(sink, &client, credentials, config, &log).await;
+            (index, sink, log, response)
+        });
+    }
+    let mut results = Vec::with_capacity(tasks.len());
+    while let Some(result) = tasks.join_next().await {
+        results.push(result?);
+    }
+    //Print in the same order as the worklogs were given
+    results.sort_by_key(|(index, _, _, _)| *index);
+
+    let mut failed_logs: Vec<Worklog> = Vec::new();
+    for (_, sink, log, response) in results {
+        match response {
+            Ok(status) if status.is_success() => {
+                println!("{} was added to {}!", log.issue_key, sink);
+            }
+            Ok(status) => {
+                println!("{} failed to be added to {}! ({})", log.issue_key, sink, status);
+                failed_logs.push(log); //TODO: Figure out how to track what has already been done?
+            }
+            Err(_) => {
+                println!("{} failed to be added to {}!", log.issue_key, sink);
+                failed_logs.push(log); //TODO: Figure out how to track what has already been done?
+            }
+        }
+    }
+
+    Ok(failed_logs)
+}
+
+async fn post_with_retry(
+    sink: WorklogSink,
+    client: &Client,
+    credentials: &Credentials,
+    config: &Config,
+    worklog: &Worklog,
+) -> anyhow::Result<StatusCode> {
+    let mut attempt = 0;
+    loop {
+        let response = sink.post(client, credentials, config, worklog).await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= MAX_RETRIES {
+            return Ok(response.status());
+        }
+        let backoff = Duration::from_secs(2u64.pow(attempt));
+        let wait = retry_after(&response).map_or(backoff, |wait| wait.max(backoff));
+        attempt += 1;
+        sleep(wait).await;
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use colored::Colorize;
+use reqwest::Client;
+
+use crate::{
+    storage::{config::retrieve_config, credentials::retrieve_credentials},
+    tempo::{
+        service::{get_user_schedule, get_worklogs},
+        structs::ScheduleDay,
+    },
+    utils::{format_hours, select_week, week_bounds},
+};
+
+pub async fn show_status(week: bool) -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    let selected_date = select_week(week)?;
+    let (from, to) = week_bounds(selected_date);
+    let from = from.format("%Y-%m-%d").to_string();
+    let to = to.format("%Y-%m-%d").to_string();
+    let client = Client::new();
+    let schedule = get_user_schedule(
+        &client,
+        config.tempo_url(),
+        config.jira_flavour(),
+        credentials.tempo_auth(config.jira_flavour()),
+        &credentials.account_id,
+        &from,
+        &to,
+    )
+    .await?;
+    let worklogs = get_worklogs(
+        &client,
+        config.tempo_url(),
+        config.jira_flavour(),
+        credentials.tempo_auth(config.jira_flavour()),
+        &credentials.account_id,
+        &from,
+        &to,
+    )
+    .await?;
+    let mut logged: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    for worklog in worklogs {
+        if let Ok(date) = NaiveDate::parse_from_str(&worklog.start_date, "%Y-%m-%d") {
+            *logged.entry(date).or_default() += worklog.time_spent_seconds;
+        }
+    }
+    println!("Logged in Tempo {} - {}:", from, to);
+    print_schedule_comparison(&schedule, &logged);
+    Ok(())
+}
+
+pub fn print_schedule_comparison(schedule: &[ScheduleDay], logged: &BTreeMap<NaiveDate, u64>) {
+    let days = schedule
+        .iter()
+        .filter_map(|day| {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?;
+            let spent = logged.get(&date).copied().unwrap_or(0);
+            format_schedule_day(date, day, spent)
+        })
+        .collect::<Vec<String>>();
+    println!("{}", days.join(", "));
+}
+
+pub fn format_schedule_day(date: NaiveDate, day: &ScheduleDay, spent: u64) -> Option<String> {
+    let weekday = date.format("%a").to_string();
+    let is_holiday = day.day_type.starts_with("HOLIDAY");
+    if spent == 0 && day.required_seconds == 0 {
+        return match &day.holiday {
+            Some(holiday) => Some(format!("{} {} ({})", weekday, "holiday".blue(), holiday.name)),
+            None if is_holiday => Some(format!("{} {}", weekday, "holiday".blue())),
+            None => None,
+        };
+    }
+    let line = if spent < day.required_seconds {
+        format!(
+            "{} {} of {} required",
+            weekday,
+            format_hours(spent).red(),
+            format_hours(day.required_seconds)
+        )
+    } else if spent > day.required_seconds {
+        format!(
+            "{} {} (+{})",
+            weekday,
+            format_hours(spent).green(),
+            format_hours(spent - day.required_seconds)
+        )
+    } else {
+        format!("{} {}", weekday, format_hours(spent).green())
+    };
+    Some(line)
+}
@@ -0,0 +1,429 @@
+use std::{collections::HashMap, fmt::Display};
+
+use inquire::{Confirm, MultiSelect, Select, Text};
+use reqwest::Client;
+use savefile::{load_file, save_file};
+use savefile_derive::Savefile;
+
+use crate::{
+    jira::service::{get_projects, JiraSite},
+    toggl::service::retrieve_workspaces,
+    utils::{KeyMatcher, DEFAULT_KEY_PATTERN},
+};
+
+use super::{
+    credentials::{prompt_jira_credentials, retrieve_credentials, Credentials},
+    storage_file,
+};
+
+#[derive(Savefile, Debug, Clone, Copy, PartialEq, Default)]
+pub enum NonBillableDefault {
+    #[default]
+    Zero,
+    TimeSpent,
+}
+
+impl Display for NonBillableDefault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonBillableDefault::Zero => write!(f, "Zero"),
+            NonBillableDefault::TimeSpent => write!(f, "Equal to time spent"),
+        }
+    }
+}
+
+#[derive(Savefile, Debug, Clone, Copy, PartialEq, Default)]
+pub enum WorklogSink {
+    #[default]
+    Tempo,
+    Jira,
+}
+
+impl Display for WorklogSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorklogSink::Tempo => write!(f, "Tempo"),
+            WorklogSink::Jira => write!(f, "Jira"),
+        }
+    }
+}
+
+// Self-hosted Jira uses personal access tokens, the older REST API and the Tempo server plugin
+#[derive(Savefile, Debug, Clone, Copy, PartialEq, Default)]
+pub enum JiraFlavour {
+    #[default]
+    Cloud,
+    Server,
+}
+
+impl Display for JiraFlavour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JiraFlavour::Cloud => write!(f, "Cloud"),
+            JiraFlavour::Server => write!(f, "Server / Data Center"),
+        }
+    }
+}
+
+// Moves an issue from one status to another once time is logged on it
+#[derive(Savefile, Debug, Clone, PartialEq)]
+pub struct TransitionRule {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Savefile, Debug, Clone, Default)]
+pub struct Config {
+    pub non_billable: NonBillableDefault,
+    #[savefile_versions = "1.."]
+    pub toggl_url: Option<String>,
+    #[savefile_versions = "1.."]
+    pub tempo_url: Option<String>,
+    #[savefile_versions = "2.."]
+    pub metadata_refresh_hours: Option<u64>,
+    #[savefile_versions = "3.."]
+    pub workspace_ids: Option<Vec<i64>>,
+    #[savefile_versions = "4.."]
+    pub webhook_secret: Option<String>,
+    #[savefile_versions = "5.."]
+    pub reports_url: Option<String>,
+    #[savefile_versions = "6.."]
+    pub rewrite_descriptions: Option<bool>,
+    #[savefile_versions = "7.."]
+    pub jira_url: Option<String>,
+    #[savefile_versions = "8.."]
+    pub import_jql: Option<String>,
+    #[savefile_versions = "9.."]
+    pub worklog_sink: Option<WorklogSink>,
+    #[savefile_versions = "9.."]
+    pub project_sinks: Option<HashMap<String, WorklogSink>>,
+    #[savefile_versions = "10.."]
+    pub key_pattern: Option<String>,
+    #[savefile_versions = "10.."]
+    pub key_projects: Option<Vec<String>>,
+    #[savefile_versions = "11.."]
+    pub jira_flavour: Option<JiraFlavour>,
+    #[savefile_versions = "12.."]
+    pub transition_rules: Option<HashMap<String, TransitionRule>>,
+}
+
+impl Config {
+    pub fn toggl_url(&self) -> &str {
+        self.toggl_url.as_deref().unwrap_or(DEFAULT_TOGGL_URL)
+    }
+
+    // The Tempo server plugin lives on the Jira site
+    pub fn tempo_url(&self) -> &str {
+        match (self.tempo_url.as_deref(), self.jira_flavour(), self.jira_url()) {
+            (Some(tempo_url), _, _) => tempo_url,
+            (None, JiraFlavour::Server, Some(jira_url)) => jira_url,
+            _ => DEFAULT_TEMPO_URL,
+        }
+    }
+
+    pub fn reports_url(&self) -> &str {
+        self.reports_url.as_deref().unwrap_or(DEFAULT_REPORTS_URL)
+    }
+
+    pub fn jira_url(&self) -> Option<&str> {
+        self.jira_url.as_deref()
+    }
+
+    pub fn jira_flavour(&self) -> JiraFlavour {
+        self.jira_flavour.unwrap_or_default()
+    }
+
+    pub fn jira_site(&self, credentials: &Credentials) -> Option<JiraSite> {
+        let url = self.jira_url()?;
+        credentials.has_jira().then(|| JiraSite {
+            url: url.to_string(),
+            flavour: self.jira_flavour(),
+            email: credentials.jira_email.to_string(),
+            token: credentials.jira_token.to_string(),
+        })
+    }
+
+    pub fn import_jql(&self) -> &str {
+        self.import_jql.as_deref().unwrap_or(DEFAULT_IMPORT_JQL)
+    }
+
+    pub fn key_pattern(&self) -> &str {
+        self.key_pattern.as_deref().unwrap_or(DEFAULT_KEY_PATTERN)
+    }
+
+    pub fn key_matcher(&self) -> anyhow::Result<KeyMatcher> {
+        KeyMatcher::new(self.key_pattern(), self.key_projects.as_deref())
+    }
+
+    // The Jira project of the key can override the sink of the profile
+    pub fn sink_for(&self, key: &str) -> WorklogSink {
+        let project = key.split('-').next().unwrap_or_default();
+        self.project_sinks
+            .as_ref()
+            .and_then(|sinks| sinks.get(project).copied())
+            .unwrap_or_else(|| self.worklog_sink.unwrap_or_default())
+    }
+
+    pub fn transition_rule(&self, key: &str) -> Option<&TransitionRule> {
+        let project = key.split('-').next().unwrap_or_default();
+        self.transition_rules.as_ref()?.get(project)
+    }
+
+    pub fn rewrite_descriptions(&self) -> bool {
+        self.rewrite_descriptions.unwrap_or(false)
+    }
+
+    pub fn metadata_refresh_hours(&self) -> u64 {
+        self.metadata_refresh_hours
+            .unwrap_or(DEFAULT_METADATA_REFRESH_HOURS)
+    }
+
+    pub fn allows_workspace(&self, workspace_id: i64) -> bool {
+        match &self.workspace_ids {
+            Some(workspace_ids) => workspace_ids.contains(&workspace_id),
+            None => true,
+        }
+    }
+}
+
+const CONFIG_FILE: &str = "toggl_sync_config.bin";
+const CONFIG_VERSION: u32 = 12;
+const DEFAULT_TOGGL_URL: &str = "https://api.track.toggl.com/api/v9";
+const DEFAULT_TEMPO_URL: &str = "https://api.tempo.io/core/3";
+const DEFAULT_REPORTS_URL: &str = "https://api.track.toggl.com/reports/api/v3";
+const DEFAULT_IMPORT_JQL: &str =
+    "(assignee = currentUser() OR sprint in openSprints()) AND resolution = Unresolved";
+const DEFAULT_METADATA_REFRESH_HOURS: u64 = 24;
+
+pub fn retrieve_config() -> anyhow::Result<Config> {
+    let existing = load_file::<Config, _>(storage_file(CONFIG_FILE), CONFIG_VERSION);
+
+    if let Ok(config) = existing {
+        return Ok(config);
+    }
+
+    Ok(Config::default())
+}
+
+pub fn store_config(config: &Config) -> anyhow::Result<()> {
+    save_file(storage_file(CONFIG_FILE), CONFIG_VERSION, config)?;
+    Ok(())
+}
+
+pub async fn choose_workspaces(config: &mut Config, credentials: &Credentials) -> anyhow::Result<()> {
+    let (username, password) = credentials.toggl_basic_auth();
+    let workspaces =
+        retrieve_workspaces(&Client::new(), config.toggl_url(), username, password).await?;
+    let options = workspaces
+        .iter()
+        .map(|workspace| format!("{} ({})", workspace.name, workspace.id))
+        .collect::<Vec<String>>();
+    let defaults = workspaces
+        .iter()
+        .enumerate()
+        .filter(|(_, workspace)| config.allows_workspace(workspace.id))
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+    let selected = MultiSelect::new("Which Toggl workspaces should be synced?", options.clone())
+        .with_default(&defaults)
+        .prompt()?;
+    let workspace_ids = workspaces
+        .iter()
+        .zip(options.iter())
+        .filter(|(_, option)| selected.contains(option))
+        .map(|(workspace, _)| workspace.id)
+        .collect::<Vec<i64>>();
+    config.workspace_ids = Some(workspace_ids);
+    Ok(())
+}
+
+pub async fn configure() -> anyhow::Result<()> {
+    let mut config = retrieve_config()?;
+    let options = vec![NonBillableDefault::Zero, NonBillableDefault::TimeSpent];
+    let starting_cursor = options
+        .iter()
+        .position(|option| *option == config.non_billable)
+        .unwrap_or(0);
+    config.non_billable = Select::new("Billable seconds for non-billable entries?", options)
+        .with_starting_cursor(starting_cursor)
+        .prompt()?;
+    config.toggl_url = prompt_url("Toggl API url?", config.toggl_url(), DEFAULT_TOGGL_URL)?;
+    let jira_url = Text::new("Jira site url? (empty to disable)")
+        .with_default(config.jira_url().unwrap_or_default())
+        .with_help_message("Like https://your-site.atlassian.net")
+        .prompt()?;
+    let jira_url = jira_url.trim().trim_end_matches('/');
+    config.jira_url = (!jira_url.is_empty()).then(|| jira_url.to_string());
+    if config.jira_url.is_some() {
+        let options = vec![JiraFlavour::Cloud, JiraFlavour::Server];
+        let starting_cursor = options
+            .iter()
+            .position(|option| *option == config.jira_flavour())
+            .unwrap_or(0);
+        config.jira_flavour = Some(
+            Select::new("Jira hosting?", options)
+                .with_starting_cursor(starting_cursor)
+                .prompt()?,
+        );
+    }
+    let tempo_default = match (config.jira_flavour(), config.jira_url()) {
+        (JiraFlavour::Server, Some(jira_url)) => jira_url.to_string(),
+        _ => DEFAULT_TEMPO_URL.to_string(),
+    };
+    config.tempo_url = prompt_url("Tempo API url?", config.tempo_url(), &tempo_default)?;
+    config.reports_url = prompt_url(
+        "Toggl reports API url?",
+        config.reports_url(),
+        DEFAULT_REPORTS_URL,
+    )?;
+    if config.jira_url.is_some() {
+        let import_jql = Text::new("JQL for importing keys?")
+            .with_default(config.import_jql())
+            .with_help_message(&format!("Default: {}", DEFAULT_IMPORT_JQL))
+            .prompt()?;
+        let import_jql = import_jql.trim();
+        config.import_jql = (!import_jql.is_empty() && import_jql != DEFAULT_IMPORT_JQL)
+            .then(|| import_jql.to_string());
+        configure_sinks(&mut config)?;
+        configure_transitions(&mut config)?;
+    }
+    configure_keys(&mut config).await?;
+    let refresh_hours = Text::new("Hours between refreshing Toggl projects, clients and tags?")
+        .with_default(&config.metadata_refresh_hours().to_string())
+        .prompt()?;
+    config.metadata_refresh_hours = Some(refresh_hours.trim().parse::<u64>()?);
+    config.rewrite_descriptions = Some(
+        Confirm::new("Rewrite Toggl descriptions to \"KEY Description\" when syncing? (y/n)")
+            .with_default(config.rewrite_descriptions())
+            .prompt()?,
+    );
+    if Confirm::new("Change the synced Toggl workspaces? (y/n)").prompt()? {
+        let credentials = retrieve_credentials().await?;
+        choose_workspaces(&mut config, &credentials).await?;
+    }
+
+    store_config(&config)?;
+    println!("Ok, configuration saved!");
+    Ok(())
+}
+
+async fn configure_keys(config: &mut Config) -> anyhow::Result<()> {
+    let key_pattern = Text::new("Regex matching issue keys?")
+        .with_default(config.key_pattern())
+        .with_help_message(&format!("Default: {}", DEFAULT_KEY_PATTERN))
+        .prompt()?;
+    let key_pattern = key_pattern.trim();
+    config.key_pattern = (!key_pattern.is_empty() && key_pattern != DEFAULT_KEY_PATTERN)
+        .then(|| key_pattern.to_string());
+    let mut current_projects = config.key_projects.clone().unwrap_or_default().join(", ");
+    if config.jira_url().is_some()
+        && Confirm::new("Import the allowed projects from Jira? (y/n)").prompt()?
+    {
+        let mut credentials = retrieve_credentials().await?;
+        if !credentials.has_jira() {
+            prompt_jira_credentials(&mut credentials, config).await?;
+        }
+        let Some(site) = config.jira_site(&credentials) else {
+            anyhow::bail!("No Jira credentials to import the projects with");
+        };
+        let projects = get_projects(&Client::new(), &site).await?;
+        current_projects = projects
+            .iter()
+            .map(|project| project.key.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+    }
+    let projects = Text::new("Allowed Jira projects? (empty for any)")
+        .with_default(&current_projects)
+        .with_help_message("Like PROJ, OPS. Keys of other projects are ignored, lowercase keys are matched too")
+        .prompt()?;
+    let projects = projects
+        .split(',')
+        .map(|project| project.trim().to_uppercase())
+        .filter(|project| !project.is_empty())
+        .collect::<Vec<String>>();
+    config.key_projects = (!projects.is_empty()).then_some(projects);
+    config.key_matcher()?;
+    Ok(())
+}
+
+fn configure_sinks(config: &mut Config) -> anyhow::Result<()> {
+    let options = vec![WorklogSink::Tempo, WorklogSink::Jira];
+    let current = config.worklog_sink.unwrap_or_default();
+    let starting_cursor = options.iter().position(|option| *option == current).unwrap_or(0);
+    config.worklog_sink = Some(
+        Select::new("Post worklogs to?", options)
+            .with_starting_cursor(starting_cursor)
+            .prompt()?,
+    );
+    let current_overrides = config
+        .project_sinks
+        .iter()
+        .flatten()
+        .map(|(project, sink)| format!("{}={}", project, sink))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let overrides = Text::new("Jira projects posting elsewhere?")
+        .with_default(&current_overrides)
+        .with_help_message("Like OPS=Jira, PROJ=Tempo")
+        .prompt()?;
+    let mut project_sinks = HashMap::new();
+    for project_sink in overrides.split(',').filter(|part| !part.trim().is_empty()) {
+        let (project, sink) = project_sink
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("{} should look like PROJ=Jira", project_sink.trim()))?;
+        let sink = match sink.trim().to_lowercase().as_str() {
+            "tempo" => WorklogSink::Tempo,
+            "jira" => WorklogSink::Jira,
+            other => anyhow::bail!("{} is not Tempo or Jira", other),
+        };
+        project_sinks.insert(project.trim().to_uppercase(), sink);
+    }
+    config.project_sinks = (!project_sinks.is_empty()).then_some(project_sinks);
+    Ok(())
+}
+
+fn configure_transitions(config: &mut Config) -> anyhow::Result<()> {
+    let current_rules = config
+        .transition_rules
+        .iter()
+        .flatten()
+        .map(|(project, rule)| format!("{}={}>{}", project, rule.from, rule.to))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let rules = Text::new("Jira transitions once time is logged?")
+        .with_default(&current_rules)
+        .with_help_message("Like PROJ=To Do>In Progress, empty for none")
+        .prompt()?;
+    let mut transition_rules = HashMap::new();
+    for rule in rules.split(',').filter(|part| !part.trim().is_empty()) {
+        let Some((project, (from, to))) = rule
+            .split_once('=')
+            .and_then(|(project, statuses)| Some((project, statuses.split_once('>')?)))
+        else {
+            anyhow::bail!("{} should look like PROJ=To Do>In Progress", rule.trim());
+        };
+        transition_rules.insert(
+            project.trim().to_uppercase(),
+            TransitionRule {
+                from: from.trim().to_string(),
+                to: to.trim().to_string(),
+            },
+        );
+    }
+    config.transition_rules = (!transition_rules.is_empty()).then_some(transition_rules);
+    Ok(())
+}
+
+fn prompt_url(message: &str, current: &str, default: &str) -> anyhow::Result<Option<String>> {
+    let url = Text::new(message)
+        .with_default(current)
+        .with_help_message(&format!("Default: {}", default))
+        .prompt()?;
+    let url = url.trim().trim_end_matches('/');
+    if url.is_empty() || url == default {
+        Ok(None)
+    } else {
+        Ok(Some(url.to_string()))
+    }
+}
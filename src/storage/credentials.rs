@@ -1,41 +1,107 @@
-use std::fs::remove_file;
+use std::{fmt::Display, fs::remove_file, path::Path};
 
-use inquire::{Confirm, Password, Text};
+use colored::Colorize;
+use inquire::{Confirm, Password, Select, Text};
+use reqwest::Client;
 use savefile::{load_file, save_file};
 use savefile_derive::Savefile;
 
-#[derive(Savefile, Debug)]
+use crate::{jira::service::get_myself, prompts};
+
+use super::{
+    config::{retrieve_config, Config, JiraFlavour},
+    storage_file,
+};
+
+#[derive(Savefile, Debug, Clone, Copy, PartialEq)]
+pub enum TogglAuth {
+    ApiToken,
+    Password,
+}
+
+impl Display for TogglAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TogglAuth::ApiToken => write!(f, "API token"),
+            TogglAuth::Password => write!(f, "Username and password"),
+        }
+    }
+}
+
+#[derive(Savefile, Debug, Clone, Default)]
 pub struct Credentials {
     pub username: String,
     pub password: String,
     pub tempo_token: String,
     pub account_id: String,
+    #[savefile_versions = "1.."]
+    pub toggl_auth: Option<TogglAuth>,
+    #[savefile_versions = "1.."]
+    pub api_token: String,
+    #[savefile_versions = "2.."]
+    pub jira_email: String,
+    #[savefile_versions = "2.."]
+    pub jira_token: String,
+}
+
+impl Credentials {
+    pub fn toggl_basic_auth(&self) -> (&str, &str) {
+        match self.toggl_auth {
+            Some(TogglAuth::ApiToken) => (&self.api_token, "api_token"),
+            _ => (&self.username, &self.password),
+        }
+    }
+
+    pub fn has_jira(&self) -> bool {
+        !self.jira_token.is_empty()
+    }
+
+    pub fn tempo_auth(&self, flavour: JiraFlavour) -> &str {
+        match flavour {
+            JiraFlavour::Cloud => &self.tempo_token,
+            JiraFlavour::Server => &self.jira_token,
+        }
+    }
 }
 
 const CRED_FILE: &str = "toggl_sync.bin";
+const CRED_VERSION: u32 = 2;
 
-pub fn retrieve_credentials() -> anyhow::Result<Credentials> {
-    let existing = load_file::<Credentials, _>(CRED_FILE, 0);
+pub async fn retrieve_credentials() -> anyhow::Result<Credentials> {
+    let existing = load_file::<Credentials, _>(storage_file(CRED_FILE), CRED_VERSION);
 
-    if let Ok(credentials) = existing {
+    if let Ok(mut credentials) = existing {
         //TODO: Decrypt pls
+        if credentials.toggl_auth.is_none() {
+            println!("Toggl now supports API tokens instead of your password!");
+            migrate_toggl_auth(&mut credentials)?;
+            if save_file(storage_file(CRED_FILE), CRED_VERSION, &credentials).is_err() {
+                println!("Failed to save credentials :(");
+            }
+        }
         return Ok(credentials);
     }
 
-    let username: String = Text::new("Toggl Username").prompt()?;
-    //TODO: Encrypt pls
-    let password = Password::new("Toggl Password").prompt()?;
-    let tempo_token: String = Text::new("Tempo token").with_help_message("https://effectsoft.atlassian.net/plugins/servlet/ac/io.tempo.jira/tempo-app#!/configuration/api-integration").prompt()?; //TODO: Link to how to create
-    let account_id: String = Text::new("Jira AccountId").with_help_message("Click your Profile menu in the upper-right, then select \"Profile\". In the URL after /people/ is your account ID.").prompt()?; //TODO: Link to how to retrieve
-
-    let credentials = Credentials {
-        username,
-        password,
-        tempo_token,
-        account_id,
-    };
+    let config = retrieve_config()?;
+    let mut credentials = Credentials::default();
+    prompt_toggl_auth(&mut credentials)?;
+    // The Tempo server plugin takes the Jira token
+    if config.jira_flavour() == JiraFlavour::Cloud {
+        credentials.tempo_token = Text::new("Tempo token").with_help_message("https://effectsoft.atlassian.net/plugins/servlet/ac/io.tempo.jira/tempo-app#!/configuration/api-integration").prompt()?; //TODO: Link to how to create
+    }
+    let mut discovered = false;
+    if config.jira_url().is_some() {
+        ask_jira_credentials(&mut credentials, config.jira_flavour())?;
+        discovered = discover_account_id(&mut credentials, &config).await?;
+    }
+    if !discovered {
+        credentials.account_id = match config.jira_flavour() {
+            JiraFlavour::Cloud => Text::new("Jira AccountId").with_help_message("Click your Profile menu in the upper-right, then select \"Profile\". In the URL after /people/ is your account ID.").prompt()?, //TODO: Link to how to retrieve
+            JiraFlavour::Server => Text::new("Jira user key").with_help_message("Shown as \"key\" at /rest/api/2/myself on your Jira site").prompt()?,
+        };
+    }
     if Confirm::new("Stay logged in? (y/n)").prompt()? {
-        let save_result = save_file(CRED_FILE, 0, &credentials);
+        let save_result = save_file(storage_file(CRED_FILE), CRED_VERSION, &credentials);
         if save_result.is_err() {
             println!("Failed to save credentials :(");
         } else {
@@ -48,7 +114,105 @@ pub fn retrieve_credentials() -> anyhow::Result<Credentials> {
     Ok(credentials)
 }
 
+fn prompt_toggl_auth(credentials: &mut Credentials) -> anyhow::Result<()> {
+    let toggl_auth = Select::new(
+        "Toggl authentication?",
+        vec![TogglAuth::ApiToken, TogglAuth::Password],
+    )
+    .prompt()?;
+    match toggl_auth {
+        TogglAuth::ApiToken => {
+            credentials.api_token = Password::new("Toggl API token")
+                .with_help_message("Found at the bottom of https://track.toggl.com/profile")
+                .prompt()?;
+        }
+        TogglAuth::Password => {
+            credentials.username = Text::new("Toggl Username").prompt()?;
+            //TODO: Encrypt pls
+            credentials.password = Password::new("Toggl Password").prompt()?;
+        }
+    }
+    credentials.toggl_auth = Some(toggl_auth);
+    Ok(())
+}
+
+fn migrate_toggl_auth(credentials: &mut Credentials) -> anyhow::Result<()> {
+    if Confirm::new("Switch to a Toggl API token? (y/n)").prompt()? {
+        credentials.api_token = Password::new("Toggl API token")
+            .with_help_message("Found at the bottom of https://track.toggl.com/profile")
+            .prompt()?;
+        credentials.toggl_auth = Some(TogglAuth::ApiToken);
+        credentials.username = String::new();
+        credentials.password = String::new();
+    } else {
+        credentials.toggl_auth = Some(TogglAuth::Password);
+    }
+    Ok(())
+}
+
+pub async fn prompt_jira_credentials(credentials: &mut Credentials, config: &Config) -> anyhow::Result<()> {
+    ask_jira_credentials(credentials, config.jira_flavour())?;
+    discover_account_id(credentials, config).await?;
+    // Only update credentials the user chose to keep
+    let cred_file = storage_file(CRED_FILE);
+    if Path::new(&cred_file).exists() && save_file(&cred_file, CRED_VERSION, credentials).is_err() {
+        println!("Failed to save credentials :(");
+    }
+    Ok(())
+}
+
+fn ask_jira_credentials(credentials: &mut Credentials, flavour: JiraFlavour) -> anyhow::Result<()> {
+    match flavour {
+        JiraFlavour::Cloud => {
+            credentials.jira_email = Text::new("Jira email").prompt()?;
+            credentials.jira_token = Password::new("Jira API token")
+                .with_help_message("https://id.atlassian.com/manage-profile/security/api-tokens")
+                .prompt()?;
+        }
+        JiraFlavour::Server => {
+            credentials.jira_email = String::new();
+            credentials.jira_token = Password::new("Jira personal access token")
+                .with_help_message("Created under Profile > Personal Access Tokens on your Jira site")
+                .prompt()?;
+        }
+    }
+    Ok(())
+}
+
+// Takes the account id from the Jira token itself, once the user recognizes the name
+pub async fn discover_account_id(credentials: &mut Credentials, config: &Config) -> anyhow::Result<bool> {
+    let Some(site) = config.jira_site(credentials) else {
+        return Ok(false);
+    };
+    let user = match get_myself(&Client::new(), &site).await {
+        Ok(user) => user,
+        Err(error) => {
+            println!("{} {}", "Failed to look up your Jira account:".red(), error);
+            return Ok(false);
+        }
+    };
+    let name = match &user.email_address {
+        Some(email) => format!("{} ({})", user.display_name, email),
+        None => user.display_name.to_string(),
+    };
+    let confirmed = prompts::confirm(
+        Confirm::new(&format!("Log time as {}? (y/n)", name.blue())).with_default(true),
+    )?;
+    if !confirmed {
+        return Ok(false);
+    }
+    if !credentials.account_id.is_empty() && credentials.account_id != user.account_id {
+        println!(
+            "Replaced account id {} with {}",
+            credentials.account_id.red(),
+            user.account_id.green()
+        );
+    }
+    credentials.account_id = user.account_id;
+    Ok(true)
+}
+
 pub fn clear_credentials() -> anyhow::Result<()> {
-    remove_file(CRED_FILE)?;
+    remove_file(storage_file(CRED_FILE))?;
     Ok(())
 }
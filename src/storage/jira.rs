@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use savefile::{load_file, save_file};
+use savefile_derive::Savefile;
+
+use super::storage_file;
+
+const VALID_KEYS_FILE: &str = "jira_valid_keys.bin";
+const ISSUE_PARENTS_FILE: &str = "jira_issue_parents.bin";
+
+// Where an issue sits in the epic hierarchy, with the timestamp of the lookup
+#[derive(Savefile, Debug, Clone)]
+pub struct IssueParent {
+    pub summary: String,
+    pub parent: Option<String>,
+    pub is_epic: bool,
+    pub checked_at: i64,
+}
+
+// Keys confirmed to exist in Jira, with the timestamp of the check
+pub fn retreive_valid_keys() -> anyhow::Result<HashMap<String, i64>> {
+    let existing = load_file::<HashMap<String, i64>, _>(storage_file(VALID_KEYS_FILE), 0);
+
+    if let Ok(keys) = existing {
+        return Ok(keys);
+    }
+
+    Ok(HashMap::new())
+}
+
+pub fn store_valid_keys(keys: &HashMap<String, i64>) -> anyhow::Result<()> {
+    save_file(storage_file(VALID_KEYS_FILE), 0, keys)?;
+    Ok(())
+}
+
+pub fn retreive_issue_parents() -> anyhow::Result<HashMap<String, IssueParent>> {
+    let existing =
+        load_file::<HashMap<String, IssueParent>, _>(storage_file(ISSUE_PARENTS_FILE), 0);
+
+    if let Ok(parents) = existing {
+        return Ok(parents);
+    }
+
+    Ok(HashMap::new())
+}
+
+pub fn store_issue_parents(parents: &HashMap<String, IssueParent>) -> anyhow::Result<()> {
+    save_file(storage_file(ISSUE_PARENTS_FILE), 0, parents)?;
+    Ok(())
+}
@@ -1,12 +1,26 @@
 use std::collections::HashMap;
 
 use colored::Colorize;
-use inquire::{MultiSelect, Text};
+use inquire::{Confirm, MultiSelect, Text};
+use reqwest::Client;
 use savefile::{load_file, save_file};
+
+use crate::{
+    jira::service::{get_issue, search_issues},
+    prompts,
+    utils::clean_key,
+};
+
+use super::{
+    config::{retrieve_config, Config},
+    credentials::{prompt_jira_credentials, retrieve_credentials, Credentials},
+    storage_file,
+};
+
 const KEY_FILE: &str = "issue_history.bin";
 
 pub fn retreive_keys() -> anyhow::Result<HashMap<String, String>> {
-    let existing = load_file::<HashMap<String, String>, _>(KEY_FILE, 0);
+    let existing = load_file::<HashMap<String, String>, _>(storage_file(KEY_FILE), 0);
 
     if let Ok(keys) = existing {
         return Ok(keys);
@@ -16,31 +30,62 @@ pub fn retreive_keys() -> anyhow::Result<HashMap<String, String>> {
 }
 
 pub fn store_keys(keys: HashMap<String, String>) -> anyhow::Result<()> {
-    let _ = save_file(KEY_FILE, 0, &keys)?;
+    let _ = save_file(storage_file(KEY_FILE), 0, &keys)?;
     Ok(())
 }
 
-pub fn add_key() -> anyhow::Result<()> {
+pub async fn add_key() -> anyhow::Result<()> {
+    let config = retrieve_config()?;
+    let credentials = match config.jira_url() {
+        Some(_) => retrieve_credentials().await?,
+        None => Credentials::default(),
+    };
+    let client = Client::new();
     let mut keys = retreive_keys()?;
     println!("Current keys:");
     for (key, value) in keys.iter() {
         println!("{}", format_key(key, value));
     }
-    let possible_key = Text::new("New key?").prompt_skippable()?;
-    match possible_key {
-        Some(key) => match Text::new("New description?").prompt_skippable()? {
-            Some(desc) => {
-                match keys.insert(key.to_string(), desc.to_string()) {
-                    Some(_) => println!("{}: {} -> Added", key, desc),
-                    None => println!("{}: {} -> ERORR - Present", key, desc),
-                }
-                add_key()?;
-            }
-            None => {}
-        },
-        None => {}
+    while let Some(key) = Text::new("New key?").prompt_skippable()? {
+        let key = clean_key(&key);
+        if key.is_empty() {
+            break;
+        }
+        let default_desc = default_description(&client, &credentials, &config, &key, "").await;
+        let Some(desc) = Text::new("New description?")
+            .with_default(&default_desc)
+            .prompt_skippable()?
+        else {
+            break;
+        };
+        match keys.insert(key.to_string(), desc.to_string()) {
+            Some(_) => println!("{}: {} -> Updated", key, desc),
+            None => println!("{}: {} -> Added", key, desc),
+        }
+    }
+    store_keys(keys)
+}
+
+// The Jira summary of the issue, or the fallback when Jira isn't set up or reachable
+pub async fn default_description(
+    client: &Client,
+    credentials: &Credentials,
+    config: &Config,
+    key: &str,
+    fallback: &str,
+) -> String {
+    let Some(site) = config.jira_site(credentials) else {
+        return fallback.to_string();
+    };
+    let issue = get_issue(client, &site, key, &["summary"]).await;
+    match issue {
+        Ok(Some(issue)) => issue.fields.summary.unwrap_or_else(|| fallback.to_string()),
+        Ok(None) => fallback.to_string(),
+        Err(_) => {
+            println!("{}", "Failed to reach Jira for the issue summary".yellow());
+            fallback.to_string()
+        }
     }
-    Ok(())
 }
 
 pub fn filter_keys() -> anyhow::Result<()> {
@@ -59,6 +104,59 @@ pub fn filter_keys() -> anyhow::Result<()> {
     store_keys(new_keys)
 }
 
+pub async fn import_keys(prune: bool) -> anyhow::Result<()> {
+    let mut credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    if config.jira_url().is_some() && !credentials.has_jira() {
+        prompt_jira_credentials(&mut credentials, &config).await?;
+    }
+    run_import(&credentials, &config, prune).await
+}
+
+pub async fn run_import(credentials: &Credentials, config: &Config, prune: bool) -> anyhow::Result<()> {
+    let Some(site) = config.jira_site(credentials) else {
+        println!("{}", "Set the Jira site url with configure first".red());
+        return Ok(());
+    };
+    let issues = search_issues(&Client::new(), &site, config.import_jql(), &["summary"]).await?;
+    let mut keys = retreive_keys()?;
+    let mut imported = 0;
+    for issue in issues.iter() {
+        if !keys.contains_key(&issue.key) {
+            let summary = issue.fields.summary.clone().unwrap_or_default();
+            println!("{}", format_key(&issue.key, &summary));
+            keys.insert(issue.key.to_string(), summary);
+            imported += 1;
+        }
+    }
+    println!(
+        "Imported {} new keys, {} were already known",
+        imported.to_string().blue(),
+        (issues.len() - imported).to_string().blue()
+    );
+
+    if prune {
+        let stale = keys
+            .iter()
+            .filter(|(key, _)| !issues.iter().any(|issue| issue.key == **key))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Vec<(String, String)>>();
+        if !stale.is_empty() {
+            println!("No longer matching:");
+            for (key, value) in stale.iter() {
+                println!("{}", format_key(key, value));
+            }
+            if prompts::confirm(Confirm::new(&format!("Remove {} keys? (y/n)", stale.len())))? {
+                for (key, _) in stale {
+                    keys.remove(&key);
+                }
+            }
+        }
+    }
+
+    store_keys(keys)
+}
+
 fn format_key(key: &str, value: &str) -> String {
     format!("{}: {}", key.blue().underline(), value.black())
 }
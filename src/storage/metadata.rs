@@ -0,0 +1,17 @@
+use savefile::{load_file, save_file};
+
+use crate::toggl::structs::TogglMetadata;
+
+use super::storage_file;
+
+const METADATA_FILE: &str = "toggl_metadata.bin";
+const METADATA_VERSION: u32 = 1;
+
+pub fn retreive_metadata() -> Option<TogglMetadata> {
+    load_file::<TogglMetadata, _>(storage_file(METADATA_FILE), METADATA_VERSION).ok()
+}
+
+pub fn store_metadata(metadata: &TogglMetadata) -> anyhow::Result<()> {
+    save_file(storage_file(METADATA_FILE), METADATA_VERSION, metadata)?;
+    Ok(())
+}
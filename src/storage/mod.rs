@@ -1,2 +1,30 @@
+use std::{fs::create_dir_all, path::Path, sync::Mutex};
+
+use lazy_static::lazy_static;
+
+pub mod config;
 pub mod credentials;
-pub mod keys;
\ No newline at end of file
+pub mod jira;
+pub mod keys;
+pub mod metadata;
+pub mod webhooks;
+
+lazy_static! {
+    static ref PROFILE: Mutex<Option<String>> = Mutex::new(None);
+}
+
+// Files of a named profile live in their own folder, the default profile keeps the working directory
+pub fn use_profile(profile: Option<String>) {
+    *PROFILE.lock().unwrap() = profile;
+}
+
+pub fn storage_file(file: &str) -> String {
+    match PROFILE.lock().unwrap().as_ref() {
+        Some(profile) => {
+            let folder = Path::new("profiles").join(profile);
+            let _ = create_dir_all(&folder);
+            folder.join(file).to_string_lossy().to_string()
+        }
+        None => file.to_string(),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use savefile::{load_file, save_file, Removed};
+use savefile_derive::Savefile;
+
+use crate::toggl::structs::TimeEntry;
+
+use super::storage_file;
+
+#[derive(Savefile, Debug, Clone)]
+pub struct QueuedEntry {
+    pub id: i64,
+    pub user_id: i64,
+    pub workspace_id: i64,
+    pub project_id: Option<i64>,
+    pub description: String,
+    pub start: String,
+    pub duration: i64,
+    pub billable: bool,
+    pub tags: Vec<String>,
+}
+
+impl QueuedEntry {
+    pub fn from_entry(entry: &TimeEntry) -> QueuedEntry {
+        QueuedEntry {
+            id: entry.id,
+            user_id: entry.user_id,
+            workspace_id: entry.workspace_id,
+            project_id: entry.project_id,
+            description: entry.description.to_string(),
+            start: entry.start.map(|start| start.to_rfc3339()).unwrap_or_default(),
+            duration: entry.duration,
+            billable: entry.billable,
+            tags: entry.tags.iter().flatten().cloned().collect(),
+        }
+    }
+
+    pub fn to_entry(&self) -> TimeEntry {
+        TimeEntry {
+            id: self.id,
+            user_id: self.user_id,
+            workspace_id: self.workspace_id,
+            project_id: self.project_id,
+            at: self.start.to_string(),
+            description: self.description.to_string(),
+            duration: self.duration,
+            duronly: false,
+            billable: self.billable,
+            start: DateTime::parse_from_rfc3339(&self.start).ok(),
+            stop: None,
+            server_deleted_at: None,
+            tags: Some(self.tags.iter().cloned().collect()),
+        }
+    }
+}
+
+// Entries received by `serve` that still need a key, and the ones already posted to Tempo
+#[derive(Savefile, Debug)]
+pub struct WebhookQueue {
+    pub pending: Vec<QueuedEntry>,
+    #[savefile_versions = "..0"]
+    pub synced_ids: Removed<Vec<i64>>,
+    // Posted entry ids with the start of the entry, dropped once the entry is too old to be edited
+    #[savefile_versions = "1.."]
+    pub synced: HashMap<i64, i64>,
+}
+
+impl Default for WebhookQueue {
+    fn default() -> Self {
+        WebhookQueue {
+            pending: Vec::new(),
+            synced_ids: Removed::new(),
+            synced: HashMap::new(),
+        }
+    }
+}
+
+impl WebhookQueue {
+    pub fn is_synced(&self, id: i64) -> bool {
+        self.synced.contains_key(&id)
+    }
+
+    pub fn mark_synced(&mut self, entry: &TimeEntry) {
+        let start = entry.start.map_or(Utc::now().timestamp(), |start| start.timestamp());
+        self.mark_synced_at(entry.id, start);
+    }
+
+    pub fn mark_synced_at(&mut self, id: i64, start: i64) {
+        self.synced.insert(id, start);
+        let oldest = (Utc::now() - Duration::days(SYNCED_RETENTION_DAYS)).timestamp();
+        self.synced.retain(|_, start| *start >= oldest);
+    }
+}
+
+const QUEUE_FILE: &str = "webhook_queue.bin";
+const QUEUE_VERSION: u32 = 1;
+const SYNCED_RETENTION_DAYS: i64 = 60;
+
+pub fn retreive_queue() -> anyhow::Result<WebhookQueue> {
+    let existing = load_file::<WebhookQueue, _>(storage_file(QUEUE_FILE), QUEUE_VERSION);
+
+    if let Ok(queue) = existing {
+        return Ok(queue);
+    }
+
+    Ok(WebhookQueue::default())
+}
+
+pub fn store_queue(queue: &WebhookQueue) -> anyhow::Result<()> {
+    save_file(storage_file(QUEUE_FILE), QUEUE_VERSION, queue)?;
+    Ok(())
+}
@@ -1,52 +1,107 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Display,
     time::Duration,
 };
 
 use crate::{
+    approve::submit_timesheet,
+    jira::{
+        service::{can_log_work, find_issues, JiraSite},
+        structs::JiraIssue,
+    },
+    prompts,
+    sink::create_worklogs,
+    status::print_schedule_comparison,
     storage::{
-        credentials::retrieve_credentials,
-        keys::{retreive_keys, store_keys},
+        config::{
+            choose_workspaces, retrieve_config, store_config, Config, JiraFlavour,
+            NonBillableDefault,
+        },
+        credentials::{prompt_jira_credentials, retrieve_credentials, Credentials},
+        jira::{retreive_valid_keys, store_valid_keys},
+        keys::{default_description, retreive_keys, store_keys},
+        webhooks::{retreive_queue, store_queue},
     },
     tempo::{
-        service::{create_worklogs, datetime_to_date_and_time},
+        service::{datetime_to_date_and_time, get_user_schedule},
         structs::Worklog,
     },
     toggl::{
         issue_completer::IssueCompleter,
-        service::{merge_filter_entries, retrieve_entries, tag_entry},
-        structs::{EntryTag, MergedEntry},
+        service::{
+            cached_metadata, describe_entries, merge_filter_entries, retrieve_entries, tag_entries,
+        },
+        structs::{BulkEditResponse, EntryTag, MergedEntry},
     },
-    utils::{clean_description, clean_key},
+    transition::{print_transitioned, transition_logged_issues},
+    utils::{clean_description, clean_key, key_distance, week_bounds, KeyMatcher},
 };
 use anyhow::Ok;
-use chrono::{Utc, Weekday};
+use chrono::{NaiveDate, Utc, Weekday};
 use colored::Colorize;
 use humantime::format_duration;
-use inquire::{Confirm, DateSelect, Text};
-use lazy_static::{__Deref, lazy_static};
-use regex::Regex;
+use inquire::{Confirm, DateSelect, Select, Text};
 use reqwest::Client;
 
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"\b[A-Z][A-Z0-9_]+-[1-9][0-9]*").unwrap();
-}
+const VALID_KEY_SECONDS: i64 = 30 * 24 * 60 * 60;
+const MAX_SUGGESTION_DISTANCE: usize = 2;
 
 pub async fn sync_toggle() -> anyhow::Result<()> {
-    let credentials = retrieve_credentials()?;
+    let mut credentials = retrieve_credentials().await?;
+    let mut config = retrieve_config()?;
+    if config.jira_url().is_some() && !credentials.has_jira() {
+        prompt_jira_credentials(&mut credentials, &config).await?;
+    }
+    if config.workspace_ids.is_none() {
+        choose_workspaces(&mut config, &credentials).await?;
+        store_config(&config)?;
+    }
+    run_sync(&credentials, &config).await
+}
+
+pub async fn run_sync(credentials: &Credentials, config: &Config) -> anyhow::Result<()> {
     let mut available_keys = retreive_keys()?;
-    let selected_date = DateSelect::new("What day do you want to sync?")
-        .with_starting_date(Utc::now().date_naive())
-        .with_week_start(Weekday::Mon)
-        .prompt()?;
+    let key_matcher = config.key_matcher()?;
+    let selected_date = prompts::date(
+        DateSelect::new("What day do you want to sync?")
+            .with_starting_date(Utc::now().date_naive())
+            .with_week_start(Weekday::Mon),
+    )?;
+    let end_date = prompts::date(
+        DateSelect::new("Until which day?")
+            .with_starting_date(selected_date)
+            .with_min_date(selected_date)
+            .with_week_start(Weekday::Mon),
+    )?;
     let client = Client::new();
-    let available_entries = retrieve_entries(
+    let (toggl_username, toggl_password) = credentials.toggl_basic_auth();
+    let queue = retreive_queue()?;
+    let mut available_entries = retrieve_entries(
         &client,
-        &credentials.username,
-        &credentials.password,
+        config.toggl_url(),
+        toggl_username,
+        toggl_password,
         selected_date,
+        end_date,
     )
-    .await?;
+    .await?
+    .into_iter()
+    .filter(|entry| config.allows_workspace(entry.workspace_id))
+    .filter(|entry| !queue.is_synced(entry.id))
+    .collect::<Vec<_>>();
+    let include_queued = !queue.pending.is_empty()
+        && prompts::confirm(Confirm::new(&format!(
+            "Include {} entries queued by serve? (y/n)",
+            queue.pending.len().to_string().blue()
+        )))?;
+    if include_queued {
+        for queued in queue.pending.iter() {
+            if !available_entries.iter().any(|entry| entry.id == queued.id) {
+                available_entries.push(queued.to_entry());
+            }
+        }
+    }
     let initial_len = available_entries.len();
     println!("Found {} Toggl entries", initial_len.to_string().blue());
     let merged_entries = merge_filter_entries(available_entries);
@@ -54,6 +109,23 @@ pub async fn sync_toggle() -> anyhow::Result<()> {
         "Merged entries into: {}",
         merged_entries.len().to_string().red()
     );
+    let metadata = cached_metadata(
+        &client,
+        config.toggl_url(),
+        toggl_username,
+        toggl_password,
+        config.metadata_refresh_hours(),
+    )
+    .await;
+    for entry in merged_entries.iter() {
+        println!(
+            "{} {}{} {}",
+            entry.start.format("%Y-%m-%d"),
+            entry.description,
+            format_project(metadata.project_label(entry.project_id)),
+            format_duration(Duration::from_secs(entry.duration as u64))
+        );
+    }
     let total_duration = merged_entries
         .iter()
         .fold(0u64, |duration, entry| duration + (entry.duration as u64));
@@ -64,106 +136,440 @@ pub async fn sync_toggle() -> anyhow::Result<()> {
             .blue()
             .underline()
     );
-    let mut accumulated_entries: Vec<Worklog> = Vec::new();
-    let mut entries_to_updated: Vec<(String, Vec<EntryTag>)> = Vec::new();
+    let mut resolved: Vec<(&MergedEntry, String, String)> = Vec::new();
     for entry in merged_entries.iter() {
-        let curr_keys = available_keys.clone();
-        let duration = Duration::from_secs(entry.duration as u64);
-        let start_datetime = entry.start;
-        let (start_date, start_time) = datetime_to_date_and_time(&start_datetime);
-        let (key, desc) = get_key_desc(entry, curr_keys)?;
+        let completer = IssueCompleter::new(available_keys.clone()).with_jira(config.jira_site(credentials));
+        let project = metadata.project_label(entry.project_id);
+        let (key, desc) = get_key_desc(entry, &key_matcher, &completer, project)?;
         if !available_keys.contains_key(&key) {
-            let key_desc = Text::new(&format!("{}, description?", key.to_string().blue()))
-                .with_default(&desc)
-                .prompt()?;
+            let default_desc = default_description(&client, credentials, config, &key, &desc).await;
+            let key_desc = prompts::text(
+                Text::new(&format!("{}, description?", key.to_string().blue()))
+                    .with_default(&default_desc),
+            )?;
             available_keys.insert(key.to_string(), key_desc);
         }
-        let worklog = Worklog {
-            author_account_id: credentials.account_id.to_string(),
-            description: desc,
-            issue_key: key.to_string(),
-            start_date,
-            start_time,
-            time_spent_seconds: duration.as_secs(),
-            date: start_datetime,
-        };
+        resolved.push((entry, key, desc));
+    }
+    if let Some(site) = config.jira_site(credentials) {
+        resolved = check_keys(&client, &site, &key_matcher, &mut available_keys, resolved).await?;
+    }
+
+    let mut accumulated_entries: Vec<Worklog> = Vec::new();
+    let mut entries_to_updated: Vec<(String, Vec<EntryTag>)> = Vec::new();
+    let mut entries_to_describe: Vec<(String, Vec<EntryTag>)> = Vec::new();
+    for (entry, key, desc) in resolved {
+        let canonical = canonical_description(&key, &desc);
+        if config.rewrite_descriptions() && entry.description != canonical {
+            entries_to_describe.push((canonical, entry.tags.clone()));
+        }
+        let worklog = build_worklog(credentials, config, entry, &key, desc);
         accumulated_entries.push(worklog);
         entries_to_updated.push((key.to_string(), entry.tags.clone()));
     }
 
-    let _failed = create_worklogs(credentials.tempo_token.to_string(), accumulated_entries).await?;
-    let client = Client::new();
+    let mut synced_per_day: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    let mut posted_per_key: BTreeMap<String, usize> = BTreeMap::new();
+    let mut posted_entries: BTreeMap<i64, i64> = BTreeMap::new();
+    for worklog in accumulated_entries.iter() {
+        *synced_per_day.entry(worklog.date.date_naive()).or_default() += worklog.time_spent_seconds;
+        *posted_per_key.entry(worklog.issue_key.to_string()).or_default() += 1;
+        for id in worklog.entry_ids.iter() {
+            posted_entries.insert(*id, worklog.date.timestamp());
+        }
+    }
+    let failed = create_worklogs(credentials, config, accumulated_entries).await?;
+    for worklog in failed.iter() {
+        if let Some(seconds) = synced_per_day.get_mut(&worklog.date.date_naive()) {
+            *seconds -= worklog.time_spent_seconds;
+        }
+        if let Some(posted) = posted_per_key.get_mut(&worklog.issue_key) {
+            *posted -= 1;
+        }
+        for id in worklog.entry_ids.iter() {
+            posted_entries.remove(id);
+        }
+    }
+    //Before tagging, so serve ignores the update webhooks of the posted entries. Reloaded as serve
+    //may have queued entries during the prompts
+    let mut queue = retreive_queue()?;
+    for (id, start) in posted_entries.iter() {
+        queue.mark_synced_at(*id, *start);
+    }
+    queue.pending.retain(|queued| !posted_entries.contains_key(&queued.id));
+    store_queue(&queue)?;
+    let mut entries_per_workspace: BTreeMap<(i64, String), Vec<i64>> = BTreeMap::new();
     for (key, tags) in entries_to_updated {
         for entry in tags {
-            let _ = tag_entry(
-                &client,
-                &credentials.username,
-                &credentials.password,
-                entry,
-                &key,
-            )
-            .await;
+            entries_per_workspace
+                .entry((entry.workspace_id, key.to_string()))
+                .or_default()
+                .push(entry.id);
+        }
+    }
+    for ((workspace_id, key), entry_ids) in entries_per_workspace {
+        let result = tag_entries(
+            &client,
+            config.toggl_url(),
+            toggl_username,
+            toggl_password,
+            workspace_id,
+            &entry_ids,
+            &key,
+        )
+        .await;
+        print_bulk_edit(result, &entry_ids, "Tagged", "tag", &key);
+    }
+    let mut descriptions_per_workspace: BTreeMap<(i64, String), Vec<i64>> = BTreeMap::new();
+    for (description, tags) in entries_to_describe {
+        for entry in tags {
+            descriptions_per_workspace
+                .entry((entry.workspace_id, description.to_string()))
+                .or_default()
+                .push(entry.id);
         }
     }
+    for ((workspace_id, description), entry_ids) in descriptions_per_workspace {
+        let result = describe_entries(
+            &client,
+            config.toggl_url(),
+            toggl_username,
+            toggl_password,
+            workspace_id,
+            &entry_ids,
+            &description,
+        )
+        .await;
+        print_bulk_edit(result, &entry_ids, "Renamed", "rename", &description);
+    }
     //TODO: Allow fixing these
     store_keys(available_keys)?;
+    if let Some(site) = config.jira_site(credentials) {
+        let logged_keys = posted_per_key
+            .into_iter()
+            .filter(|(_, posted)| *posted > 0)
+            .map(|(key, _)| key)
+            .collect::<Vec<String>>();
+        let transitioned = transition_logged_issues(&client, &site, config, &logged_keys).await;
+        print_transitioned(&transitioned);
+    }
+
+    let schedule = get_user_schedule(
+        &client,
+        config.tempo_url(),
+        config.jira_flavour(),
+        credentials.tempo_auth(config.jira_flavour()),
+        &credentials.account_id,
+        &selected_date.format("%Y-%m-%d").to_string(),
+        &end_date.format("%Y-%m-%d").to_string(),
+    )
+    .await;
+    match schedule {
+        std::result::Result::Ok(schedule) => {
+            println!("Synced compared to your schedule:");
+            print_schedule_comparison(&schedule, &synced_per_day);
+        }
+        Err(_) => println!("{}", "Failed to retrieve your Tempo schedule".red()),
+    }
+
+    if end_date > selected_date
+        && config.jira_flavour() == JiraFlavour::Cloud
+        && prompts::confirm(
+            Confirm::new("Submit the timesheet for approval? (y/n)").with_default(false),
+        )?
+    {
+        //Every week the synced range touches
+        let mut week = week_bounds(selected_date).0;
+        while week <= end_date {
+            submit_timesheet(credentials, config, week).await?;
+            week += chrono::Duration::days(7);
+        }
+    }
 
     Ok(())
 }
 
-fn get_possible_key_tag(entry: &MergedEntry) -> Option<String> {
+fn get_possible_key_tag(entry: &MergedEntry, key_matcher: &KeyMatcher) -> Option<String> {
     let tags = entry
         .tags
         .iter()
         .filter(|x| x.tags.is_some())
         .flat_map(|entry_tags| entry_tags.tags.as_ref().unwrap())
         .collect::<HashSet<&String>>();
-    let possible_key = tags.iter().find(|tag| RE.is_match(tag));
-    if let Some(key) = possible_key {
-        Some(key.to_string())
+    tags.iter().find_map(|tag| key_matcher.find(tag))
+}
+
+enum KeyProblem {
+    Missing,
+    Resolved(String),
+    NotLoggable,
+}
+
+impl Display for KeyProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyProblem::Missing => write!(f, "was not found in Jira"),
+            KeyProblem::Resolved(status) => write!(f, "is resolved ({})", status),
+            KeyProblem::NotLoggable => write!(f, "doesn't allow logging time"),
+        }
+    }
+}
+
+// Missing, resolved and non-loggable keys are replaced or skipped, loggable ones are cached for a while
+async fn check_keys<'a>(
+    client: &Client,
+    site: &JiraSite,
+    key_matcher: &KeyMatcher,
+    available_keys: &mut HashMap<String, String>,
+    resolved: Vec<(&'a MergedEntry, String, String)>,
+) -> anyhow::Result<Vec<(&'a MergedEntry, String, String)>> {
+    let mut valid_keys = retreive_valid_keys()?;
+    let now = Utc::now().timestamp();
+    let mut issues: HashMap<String, JiraIssue> = HashMap::new();
+    let mut replacements: HashMap<String, Option<String>> = HashMap::new();
+    let mut accepted: HashSet<String> = HashSet::new();
+    let is_cached = |valid_keys: &HashMap<String, i64>, key: &str| {
+        valid_keys
+            .get(key)
+            .is_some_and(|checked_at| now - checked_at < VALID_KEY_SECONDS)
+    };
+    let mut unchecked = resolved
+        .iter()
+        .map(|(_, key, _)| key.to_string())
+        .filter(|key| !is_cached(&valid_keys, key))
+        .collect::<BTreeSet<String>>();
+    while !unchecked.is_empty() {
+        let keys = unchecked.into_iter().collect::<Vec<String>>();
+        let found = find_issues(client, site, &keys, &["summary", "status", "resolution"]).await;
+        let std::result::Result::Ok(found) = found else {
+            println!("{}", "Failed to reach Jira, the remaining keys are not validated".red());
+            break;
+        };
+        for issue in found {
+            issues.insert(issue.key.to_string(), issue);
+        }
+        unchecked = BTreeSet::new();
+        let mut problems: Vec<(&String, KeyProblem)> = Vec::new();
+        for key in keys.iter() {
+            let problem = match issues.get(key) {
+                None => Some(KeyProblem::Missing),
+                Some(issue) if issue.is_resolved() => Some(KeyProblem::Resolved(issue.status_name())),
+                Some(_) => {
+                    if can_log_work(client, site, key).await.unwrap_or(true) {
+                        valid_keys.insert(key.to_string(), now);
+                        accepted.insert(key.to_string());
+                        None
+                    } else {
+                        Some(KeyProblem::NotLoggable)
+                    }
+                }
+            };
+            if let Some(problem) = problem {
+                problems.push((key, problem));
+            }
+        }
+        for (key, problem) in problems {
+            valid_keys.remove(key);
+            let replacement = prompt_replacement(key, &problem, key_matcher, available_keys, site)?;
+            if replacement.as_deref() == Some(key.as_str()) {
+                continue;
+            }
+            if let KeyProblem::Missing = problem {
+                available_keys.remove(key);
+            }
+            if let Some(new_key) = &replacement
+                && !accepted.contains(new_key)
+                && !is_cached(&valid_keys, new_key)
+            {
+                unchecked.insert(new_key.to_string());
+            }
+            replacements.insert(key.to_string(), replacement);
+        }
+    }
+    store_valid_keys(&valid_keys)?;
+
+    Ok(resolved
+        .into_iter()
+        .filter_map(|(entry, mut key, desc)| {
+            let mut seen = HashSet::new();
+            while let Some(replacement) = replacements.get(&key) {
+                if !seen.insert(key.to_string()) {
+                    return None;
+                }
+                key = replacement.clone()?;
+            }
+            Some((entry, key, desc))
+        })
+        .collect())
+}
+
+fn prompt_replacement(
+    key: &str,
+    problem: &KeyProblem,
+    key_matcher: &KeyMatcher,
+    available_keys: &HashMap<String, String>,
+    site: &JiraSite,
+) -> anyhow::Result<Option<String>> {
+    let mut suggestions = available_keys
+        .iter()
+        .filter(|(known, _)| known.as_str() != key)
+        .map(|(known, desc)| (key_distance(key, known), known, desc))
+        .filter(|(distance, _, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect::<Vec<_>>();
+    suggestions.sort();
+    let mut options = suggestions
+        .iter()
+        .take(3)
+        .map(|(_, known, desc)| format!("{}: {}", known, desc))
+        .collect::<Vec<String>>();
+    let other = "Other key".to_string();
+    let keep = format!("Keep {}", key);
+    let skip = "Skip these entries".to_string();
+    options.extend([other.to_string(), keep.to_string(), skip.to_string()]);
+    let selected = prompts::select(Select::new(
+        &format!("{} {}, use instead?", key.red(), problem),
+        options,
+    ))?;
+    if selected == skip {
+        Ok(None)
+    } else if selected == keep {
+        Ok(Some(key.to_string()))
+    } else if selected == other {
+        let completer = IssueCompleter::new(available_keys.clone()).with_jira(Some(site.clone()));
+        Ok(Some(prompt_key(&completer, key_matcher)?))
+    } else {
+        Ok(Some(clean_key(&selected)))
+    }
+}
+
+fn print_bulk_edit(
+    result: anyhow::Result<BulkEditResponse>,
+    entry_ids: &[i64],
+    done: &str,
+    action: &str,
+    value: &str,
+) {
+    match result {
+        std::result::Result::Ok(result) => {
+            if !result.success.is_empty() {
+                println!("{} {} Toggl entries with {}", done, result.success.len(), value.blue());
+            }
+            for failure in result.failure {
+                println!(
+                    "{} {} with {}: {}",
+                    format!("Failed to {} Toggl entry", action).red(),
+                    failure.id,
+                    value,
+                    failure.message
+                );
+            }
+        }
+        Err(_) => println!(
+            "{} {} with {}",
+            format!("Failed to {} Toggl entries", action).red(),
+            entry_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            value
+        ),
+    }
+}
+
+pub fn canonical_description(key: &str, desc: &str) -> String {
+    if desc.is_empty() {
+        key.to_string()
     } else {
-        None
+        format!("{} {}", key, desc)
+    }
+}
+
+fn format_project(project: Option<String>) -> String {
+    match project {
+        Some(project) => format!(" ({})", project.dimmed()),
+        None => String::new(),
+    }
+}
+
+pub fn build_worklog(
+    credentials: &Credentials,
+    config: &Config,
+    entry: &MergedEntry,
+    key: &str,
+    desc: String,
+) -> Worklog {
+    let duration = Duration::from_secs(entry.duration as u64);
+    let (start_date, start_time) = datetime_to_date_and_time(&entry.start);
+    let billable_seconds = match config.non_billable {
+        NonBillableDefault::Zero => entry.billable_duration as u64,
+        NonBillableDefault::TimeSpent => duration.as_secs(),
+    };
+    Worklog {
+        author_account_id: credentials.account_id.to_string(),
+        description: desc,
+        issue_key: key.to_string(),
+        start_date,
+        start_time,
+        time_spent_seconds: duration.as_secs(),
+        billable_seconds,
+        date: entry.start,
+        entry_ids: entry.tags.iter().map(|tag| tag.id).collect(),
     }
 }
 
+// Asks again until the key fits the key pattern and the allowed projects
+pub fn prompt_key(completer: &IssueCompleter, key_matcher: &KeyMatcher) -> anyhow::Result<String> {
+    loop {
+        let key = clean_key(&prompts::text(Text::new("Key?").with_autocomplete(completer.clone()))?);
+        if key_matcher.is_key(&key) {
+            return Ok(key);
+        }
+        println!("{} is not an allowed key", key.red());
+    }
+}
+
+// Key from the tags or the description, without asking
+pub fn resolve_key_desc(entry: &MergedEntry, key_matcher: &KeyMatcher) -> (Option<String>, String) {
+    let key = get_possible_key_tag(entry, key_matcher).or_else(|| key_matcher.find(&entry.description));
+    let desc = clean_description(&key_matcher.remove(&entry.description));
+    (key, desc)
+}
+
 fn get_key_desc(
     entry: &MergedEntry,
-    curr_keys: HashMap<String, String>,
+    key_matcher: &KeyMatcher,
+    completer: &IssueCompleter,
+    project: Option<String>,
 ) -> anyhow::Result<(String, String)> {
-    let possible_key = RE.captures(&entry.description);
-    let mut key: Option<String> = get_possible_key_tag(entry);
-    let mut desc: String = clean_description(RE.replace(&entry.description, "").deref());
+    let (mut key, mut desc) = resolve_key_desc(entry, key_matcher);
     let duration = Duration::from_secs(entry.duration as u64);
     let edit_requested: bool;
-    if possible_key.is_none() && let Some(captures) = possible_key && let Some(key_match) = captures.get(0) {
-        let possible_key = key_match.as_str();
-        desc = clean_description(&entry.description.replace(key_match.as_str(), ""));
-        key = Some(possible_key.to_string());
-    }
     if let Some(pos_key) = &key {
-        edit_requested =
-            Confirm::new(&format!("{}: {}. Edit? (y/n)", pos_key.red(), desc.green())).prompt()?;
+        edit_requested = prompts::confirm(Confirm::new(&format!(
+            "{}: {}{}. Edit? (y/n)",
+            pos_key.red(),
+            desc.green(),
+            format_project(project.clone())
+        )))?;
     } else {
         println!(
-            "Missing key! Desc: {}, Duration: {}",
+            "Missing key! Desc: {}{}, Duration: {}",
             entry.description.red().underline(),
+            format_project(project.clone()),
             format_duration(duration).to_string().blue().underline()
         );
-        key = Some(
-            Text::new("Key?")
-                .with_autocomplete(IssueCompleter::new(curr_keys.clone()))
-                .prompt()?,
-        );
-        edit_requested = Confirm::new("Edit? (y/n)").prompt()?;
+        key = Some(prompt_key(completer, key_matcher)?);
+        edit_requested = prompts::confirm(Confirm::new("Edit? (y/n)"))?;
     }
     if edit_requested {
-        desc = Text::new("Description?").with_default(&desc).prompt()?;
+        desc = prompts::text(Text::new("Description?").with_default(&desc))?;
     }
     match key {
         Some(new_key) => {
             let clean_key = clean_key(&new_key.to_owned());
             Ok((clean_key, desc))
         }
-        None => get_key_desc(entry, curr_keys),
+        None => get_key_desc(entry, key_matcher, completer, project),
     }
 }
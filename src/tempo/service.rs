@@ -1,9 +1,18 @@
 use anyhow::Ok;
 use chrono::{DateTime, Utc};
-use reqwest::{Client, Method, Response, StatusCode};
+use reqwest::{Client, Method, Response};
 
-use super::structs::Worklog;
-const TEMPO_URL: &str = "https://api.tempo.io/core/3/worklogs";
+use crate::storage::config::JiraFlavour;
+
+use super::structs::{
+    ReviewersResponse, ScheduleDay, ScheduleResponse, ServerScheduleResponse, ServerWorklog,
+    ServerWorklogResult, ServerWorklogSearch, SubmitApprovalRequest, TempoUser,
+    TimesheetApproval, WorkLogResponse, WorkLogResult, Worklog,
+};
+
+// The Tempo server plugin is served from the Jira site
+const SERVER_TIMESHEETS_PATH: &str = "/rest/tempo-timesheets/4";
+const SERVER_CORE_PATH: &str = "/rest/tempo-core/1";
 
 pub fn datetime_to_date_and_time(date: &DateTime<Utc>) -> (String, String) {
     let start_date = date.format("%Y-%m-%d").to_string();
@@ -11,37 +20,24 @@ pub fn datetime_to_date_and_time(date: &DateTime<Utc>) -> (String, String) {
     (start_date, start_time)
 }
 
-pub async fn create_worklogs(token: String, worklogs: Vec<Worklog>) -> anyhow::Result<Vec<Worklog>> {
-    let tempo_client = Client::new();
-    let mut failed_logs: Vec<Worklog> = Vec::new();
-    for log in worklogs {
-        let issue_key = log.issue_key.to_string();
-        let response = create_worklog(
-            &tempo_client,
-            token.to_string(),
-            &log,
-        )
-        .await;
-        if let std::result::Result::Ok(res) = response {
-            //TODO: Parse result on issue
-            if res.status() != StatusCode::OK {
-                println!("{} failed to be added to tempo!", issue_key);
-                failed_logs.push(log); //TODO: Figure out how to track what has already been done?
-            } else {
-                println!("{} was added to tempo!", issue_key);
-            }
-        } else {
-            println!("{} failed to be added to tempo!", issue_key);
-                failed_logs.push(log); //TODO: Figure out how to track what has already been done?
-        }
+pub async fn create_worklog(
+    client: &Client,
+    base_url: &str,
+    flavour: JiraFlavour,
+    token: String,
+    work_log: &Worklog,
+) -> anyhow::Result<Response> {
+    if flavour == JiraFlavour::Server {
+        let response = client
+            .request(Method::POST, format!("{}{}/worklogs", base_url, SERVER_TIMESHEETS_PATH))
+            .json(&ServerWorklog::from_worklog(work_log))
+            .bearer_auth(token)
+            .send()
+            .await?;
+        return Ok(response);
     }
-
-    Ok(failed_logs)
-}
-
-pub async fn create_worklog(client: &Client, token: String, work_log: &Worklog) -> anyhow::Result<Response> {
     let response = client
-        .request(Method::POST, TEMPO_URL)
+        .request(Method::POST, format!("{}/worklogs", base_url))
         .json(&work_log)
         .bearer_auth(token)
         .send()
@@ -49,15 +45,149 @@ pub async fn create_worklog(client: &Client, token: String, work_log: &Worklog)
     Ok(response)
 }
 
-// pub async fn get_worklogs(client: &Client, token: String, account_id: String, from: String, to: String) -> anyhow::Result<Vec<WorkLogResult>> {
-//     let worklogs = client
-//         .request(Method::GET, TEMPO_URL.to_string() + "/user/" + &account_id)
-//         .query(&[("limit", "1000".to_string()), ("from", from), ("to", to)])
-//         .bearer_auth(token)
-//         .send()
-//         .await?
-//         .json::<WorkLogResponse>()
-//         .await?;
-
-//     Ok(worklogs.results)
-// }
+pub async fn get_worklogs(
+    client: &Client,
+    base_url: &str,
+    flavour: JiraFlavour,
+    token: &str,
+    account_id: &str,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Vec<WorkLogResult>> {
+    if flavour == JiraFlavour::Server {
+        let search = ServerWorklogSearch {
+            from: from.to_string(),
+            to: to.to_string(),
+            worker: vec![account_id.to_string()],
+        };
+        let worklogs = client
+            .request(
+                Method::POST,
+                format!("{}{}/worklogs/search", base_url, SERVER_TIMESHEETS_PATH),
+            )
+            .json(&search)
+            .bearer_auth(token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<ServerWorklogResult>>()
+            .await?;
+        return Ok(worklogs.into_iter().map(ServerWorklogResult::into_result).collect());
+    }
+    let worklogs = client
+        .request(Method::GET, format!("{}/worklogs/user/{}", base_url, account_id))
+        .query(&[("limit", "1000"), ("from", from), ("to", to)])
+        .bearer_auth(token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<WorkLogResponse>()
+        .await?;
+
+    Ok(worklogs.results)
+}
+
+pub async fn get_user_schedule(
+    client: &Client,
+    base_url: &str,
+    flavour: JiraFlavour,
+    token: &str,
+    account_id: &str,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Vec<ScheduleDay>> {
+    if flavour == JiraFlavour::Server {
+        let schedule = client
+            .request(Method::GET, format!("{}{}/user/schedule", base_url, SERVER_CORE_PATH))
+            .query(&[("user", account_id), ("from", from), ("to", to)])
+            .bearer_auth(token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ServerScheduleResponse>()
+            .await?;
+        return Ok(schedule.days);
+    }
+    let schedule = client
+        .request(Method::GET, format!("{}/user-schedule/{}", base_url, account_id))
+        .query(&[("from", from), ("to", to)])
+        .bearer_auth(token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<ScheduleResponse>()
+        .await?;
+
+    Ok(schedule.results)
+}
+
+pub async fn get_approval(
+    client: &Client,
+    base_url: &str,
+    token: &str,
+    account_id: &str,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<TimesheetApproval> {
+    let approval = client
+        .request(
+            Method::GET,
+            format!("{}/timesheet-approvals/user/{}", base_url, account_id),
+        )
+        .query(&[("from", from), ("to", to)])
+        .bearer_auth(token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TimesheetApproval>()
+        .await?;
+
+    Ok(approval)
+}
+
+pub async fn get_reviewers(
+    client: &Client,
+    base_url: &str,
+    token: &str,
+    account_id: &str,
+) -> anyhow::Result<Vec<TempoUser>> {
+    let reviewers = client
+        .request(
+            Method::GET,
+            format!("{}/timesheet-approvals/user/{}/reviewers", base_url, account_id),
+        )
+        .bearer_auth(token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<ReviewersResponse>()
+        .await?;
+
+    Ok(reviewers.results)
+}
+
+pub async fn submit_approval(
+    client: &Client,
+    base_url: &str,
+    token: &str,
+    account_id: &str,
+    from: &str,
+    to: &str,
+    request: &SubmitApprovalRequest,
+) -> anyhow::Result<TimesheetApproval> {
+    let approval = client
+        .request(
+            Method::POST,
+            format!("{}/timesheet-approvals/user/{}/submit", base_url, account_id),
+        )
+        .query(&[("from", from), ("to", to)])
+        .json(request)
+        .bearer_auth(token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TimesheetApproval>()
+        .await?;
+
+    Ok(approval)
+}
@@ -13,8 +13,13 @@ pub struct Worklog {
     pub start_time: String,
     #[serde(rename = "timeSpentSeconds")]
     pub time_spent_seconds: u64,
+    #[serde(rename = "billableSeconds")]
+    pub billable_seconds: u64,
     #[serde(skip_serializing)]
     pub date: DateTime<Utc>,
+    // Toggl entries merged into this worklog
+    #[serde(skip_serializing)]
+    pub entry_ids: Vec<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -35,6 +40,133 @@ pub struct WorkLogResult {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-struct WorkLogResponse {
-    results: Vec<WorkLogResult>
-}
\ No newline at end of file
+pub struct WorkLogResponse {
+    pub results: Vec<WorkLogResult>
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TempoUser {
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReviewersResponse {
+    pub results: Vec<TempoUser>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApprovalPeriod {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApprovalStatus {
+    pub key: String,
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TimesheetApproval {
+    pub period: ApprovalPeriod,
+    #[serde(rename = "requiredSeconds")]
+    pub required_seconds: u64,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: u64,
+    pub status: ApprovalStatus,
+    pub reviewer: Option<TempoUser>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SubmitApprovalRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(rename = "reviewerAccountId")]
+    pub reviewer_account_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Holiday {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScheduleDay {
+    pub date: String,
+    #[serde(rename = "requiredSeconds")]
+    pub required_seconds: u64,
+    #[serde(rename = "type")]
+    pub day_type: String,
+    pub holiday: Option<Holiday>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScheduleResponse {
+    pub results: Vec<ScheduleDay>,
+}
+
+// The Tempo Timesheets server plugin takes the user key as worker and the issue key as origin task
+#[derive(Serialize, Debug)]
+pub struct ServerWorklog {
+    pub worker: String,
+    #[serde(rename = "originTaskId")]
+    pub origin_task_id: String,
+    pub started: String,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: u64,
+    #[serde(rename = "billableSeconds")]
+    pub billable_seconds: u64,
+    pub comment: String,
+}
+
+impl ServerWorklog {
+    pub fn from_worklog(worklog: &Worklog) -> ServerWorklog {
+        ServerWorklog {
+            worker: worklog.author_account_id.to_string(),
+            origin_task_id: worklog.issue_key.to_string(),
+            started: format!("{} {}.000", worklog.start_date, worklog.start_time),
+            time_spent_seconds: worklog.time_spent_seconds,
+            billable_seconds: worklog.billable_seconds,
+            comment: worklog.description.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ServerWorklogSearch {
+    pub from: String,
+    pub to: String,
+    pub worker: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ServerWorklogResult {
+    pub issue: Issue,
+    pub started: String,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: u64,
+    pub comment: Option<String>,
+}
+
+impl ServerWorklogResult {
+    // Started looks like 2026-10-12 09:00:00.000
+    pub fn into_result(self) -> WorkLogResult {
+        let (start_date, time) = self.started.split_once(' ').unwrap_or((&self.started, "00:00:00"));
+        let start_time = time.split('.').next().unwrap_or(time);
+        WorkLogResult {
+            issue: self.issue,
+            start_date: start_date.to_string(),
+            start_time: start_time.to_string(),
+            time_spent_seconds: self.time_spent_seconds,
+            description: self.comment.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ServerScheduleResponse {
+    pub days: Vec<ScheduleDay>,
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use colored::Colorize;
+use humantime::format_duration;
+use inquire::Text;
+use reqwest::Client;
+
+use crate::{
+    prompts,
+    pull::select_entry_workspace,
+    storage::{
+        config::{retrieve_config, Config},
+        credentials::{retrieve_credentials, Credentials},
+        keys::{retreive_keys, store_keys},
+    },
+    sync::{canonical_description, prompt_key},
+    toggl::{
+        issue_completer::IssueCompleter,
+        service::{create_entry, retrieve_current_entry, stop_entry},
+        structs::NewTimeEntry,
+    },
+};
+
+pub async fn start_timer() -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    run_start(&credentials, &config).await
+}
+
+pub async fn stop_timer() -> anyhow::Result<()> {
+    let credentials = retrieve_credentials().await?;
+    let config = retrieve_config()?;
+    run_stop(&credentials, &config).await
+}
+
+pub async fn run_start(credentials: &Credentials, config: &Config) -> anyhow::Result<()> {
+    let mut available_keys = retreive_keys()?;
+    let completer = IssueCompleter::new(available_keys.clone()).with_jira(config.jira_site(credentials));
+    let key = prompt_key(&completer, &config.key_matcher()?)?;
+    let default_desc = available_keys.get(&key).cloned().unwrap_or_default();
+    let desc = prompts::text(Text::new("Description?").with_default(&default_desc))?;
+    if !available_keys.contains_key(&key) {
+        available_keys.insert(key.to_string(), desc.to_string());
+        store_keys(available_keys)?;
+    }
+
+    let client = Client::new();
+    let (toggl_username, toggl_password) = credentials.toggl_basic_auth();
+    let workspace_id =
+        select_entry_workspace(&client, config, toggl_username, toggl_password).await?;
+    let description = if config.rewrite_descriptions() {
+        canonical_description(&key, &desc)
+    } else {
+        desc
+    };
+    // A negative duration keeps the entry running
+    let entry = NewTimeEntry {
+        created_with: "toggl_sync".to_string(),
+        description,
+        tags: vec![key.to_string()],
+        start: Utc::now().to_rfc3339(),
+        duration: -1,
+        workspace_id,
+        project_id: None,
+    };
+    create_entry(&client, config.toggl_url(), toggl_username, toggl_password, &entry).await?;
+    println!("Started {}: {}", key.blue(), entry.description.green());
+    Ok(())
+}
+
+pub async fn run_stop(credentials: &Credentials, config: &Config) -> anyhow::Result<()> {
+    let client = Client::new();
+    let (toggl_username, toggl_password) = credentials.toggl_basic_auth();
+    let current =
+        retrieve_current_entry(&client, config.toggl_url(), toggl_username, toggl_password).await?;
+    let Some(current) = current else {
+        println!("No Toggl entry is running");
+        return Ok(());
+    };
+    let stopped = stop_entry(
+        &client,
+        config.toggl_url(),
+        toggl_username,
+        toggl_password,
+        &current,
+    )
+    .await?;
+    println!(
+        "Stopped {} after {}",
+        stopped.description.green(),
+        format_duration(Duration::from_secs(stopped.duration.max(0) as u64))
+            .to_string()
+            .blue()
+    );
+    Ok(())
+}
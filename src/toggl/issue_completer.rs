@@ -1,11 +1,28 @@
-use std::collections::{HashMap};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
 
 use colored::Colorize;
 use inquire::{Autocomplete, CustomUserError, autocompletion::Replacement};
+use reqwest::Client;
+
+use crate::jira::service::{quick_search_issues, JiraSite};
+
+const MIN_LOCAL_MATCHES: usize = 5;
+const MIN_SEARCH_LENGTH: usize = 3;
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(400);
+const MAX_REMOTE_RESULTS: u32 = 10;
 
 #[derive(Clone, Default)]
 pub struct IssueCompleter {
-    prev_issues: Vec<IssueKey>
+    prev_issues: Vec<IssueKey>,
+    jira: Option<JiraSearch>
 }
 
 #[derive(Clone, Default)]
@@ -16,33 +33,150 @@ struct IssueKey {
     pub order: u64
 }
 
+#[derive(Clone)]
+enum SearchState {
+    // With the narrowed results of an earlier query until the search is done
+    Running(Vec<IssueKey>),
+    Found(Vec<IssueKey>),
+    Failed
+}
+
+type Searches = Arc<Mutex<HashMap<String, SearchState>>>;
+
+// Searches run on their own thread once typing pauses, the prompt never waits for Jira
+#[derive(Clone)]
+struct JiraSearch {
+    site: JiraSite,
+    queries: Option<Sender<String>>,
+    // Results per query, so typing back and forth doesn't search again
+    searches: Searches
+}
+
+impl IssueKey {
+    fn new(key: &str, desc: &str) -> IssueKey {
+        let (_, order_str) = key.split_once('-').unwrap_or(("", "0"));
+        let order = order_str.parse::<u64>().unwrap_or(0);
+        let hash = (key.to_string() + desc).to_lowercase();
+        IssueKey {
+            hash,
+            key: key.to_string(),
+            desc: desc.to_string(),
+            order
+        }
+    }
+}
+
 impl IssueCompleter {
     pub fn new(prev: HashMap<String, String>) -> IssueCompleter {
-        let mut prev_issues = prev.iter().map(|(key, value)| {
-            let (_, order_str) = key.split_once('-').unwrap_or(("", "0")); 
-            let order = order_str.parse::<u64>().unwrap_or(0);
-            let hash = (key.to_string() + value).to_lowercase();
-            IssueKey {
-                hash,
-                key: key.to_string(),
-                desc: value.to_string(),
-                order
-            }
-        }).collect::<Vec<_>>();
+        let mut prev_issues = prev.iter().map(|(key, value)| IssueKey::new(key, value)).collect::<Vec<_>>();
         prev_issues.sort_by(|a, b| a.order.cmp(&b.order));
-        IssueCompleter { prev_issues }
+        IssueCompleter { prev_issues, jira: None }
+    }
+
+    // Falls back to searching Jira when few previous keys match
+    pub fn with_jira(mut self, site: Option<JiraSite>) -> IssueCompleter {
+        self.jira = site.map(|site| JiraSearch {
+            site,
+            queries: None,
+            searches: Arc::default()
+        });
+        self
     }
 }
 
+impl JiraSearch {
+    fn search(&mut self, input: &str) -> SearchState {
+        let query = input.trim().to_lowercase();
+        let mut states = self.searches.lock().unwrap();
+        match states.get(&query) {
+            Some(SearchState::Running(_)) => {}
+            Some(state) => return state.clone(),
+            None => {
+                let queries = self
+                    .queries
+                    .get_or_insert_with(|| spawn_searches(self.site.clone(), self.searches.clone()));
+                let state = match queries.send(query.to_string()) {
+                    Ok(_) => SearchState::Running(Vec::new()),
+                    Err(_) => SearchState::Failed
+                };
+                states.insert(query.to_string(), state);
+            }
+        }
+        // Meanwhile narrow down the results of the closest earlier query
+        let narrowed = states
+            .iter()
+            .filter(|(cached_query, _)| query.starts_with(cached_query.as_str()))
+            .filter_map(|(cached_query, state)| match state {
+                SearchState::Found(issues) => Some((cached_query, issues)),
+                _ => None
+            })
+            .max_by_key(|(cached_query, _)| cached_query.len())
+            .map(|(_, issues)| issues.iter().filter(|issue| issue.hash.contains(&query)).cloned().collect())
+            .unwrap_or_default();
+        SearchState::Running(narrowed)
+    }
+}
+
+// Searches the last query once no new one came in for a while, stops when the completer is dropped
+fn spawn_searches(site: JiraSite, searches: Searches) -> Sender<String> {
+    let (sender, receiver) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else {
+            return;
+        };
+        let client = Client::new();
+        while let Ok(mut query) = receiver.recv() {
+            loop {
+                match receiver.recv_timeout(SEARCH_DEBOUNCE) {
+                    // Skipped, typing it again queues it again
+                    Ok(newer) => {
+                        searches.lock().unwrap().remove(&query);
+                        query = newer;
+                    }
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return
+                }
+            }
+            let escaped = query.replace('\\', "\\\\").replace('"', "\\\"");
+            let jql = format!("summary ~ \"{}*\" ORDER BY updated DESC", escaped);
+            let state = match runtime.block_on(quick_search_issues(&client, &site, &jql, MAX_REMOTE_RESULTS)) {
+                Ok(issues) => SearchState::Found(issues
+                    .iter()
+                    .map(|issue| IssueKey::new(&issue.key, issue.fields.summary.as_deref().unwrap_or_default()))
+                    .collect()),
+                Err(_) => SearchState::Failed
+            };
+            searches.lock().unwrap().insert(query, state);
+        }
+    });
+    sender
+}
+
 impl Autocomplete for IssueCompleter {
     fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
-
-        Ok(self.prev_issues
+        let mut suggestions = self.prev_issues
             .iter()
             .filter(|p| p.hash.contains(&input.to_lowercase()))
             .take(20)
             .map(|p| format!("{}: {}", p.key, p.desc.black()))
-            .collect())
+            .collect::<Vec<_>>();
+        if suggestions.len() < MIN_LOCAL_MATCHES && input.trim().len() >= MIN_SEARCH_LENGTH && let Some(jira) = self.jira.as_mut() {
+            let known = self.prev_issues.iter().map(|p| p.key.as_str()).collect::<HashSet<_>>();
+            let format_issues = |issues: Vec<IssueKey>| issues
+                .into_iter()
+                .filter(|issue| !known.contains(issue.key.as_str()))
+                .map(|issue| format!("{}: {} {}", issue.key, issue.desc.black(), "(Jira)".blue()))
+                .collect::<Vec<_>>();
+            match jira.search(input) {
+                SearchState::Found(issues) => suggestions.extend(format_issues(issues)),
+                SearchState::Running(narrowed) => {
+                    suggestions.extend(format_issues(narrowed));
+                    suggestions.push("Searching Jira...".blue().to_string());
+                }
+                SearchState::Failed => suggestions.push("Jira search failed".red().to_string())
+            }
+        }
+        Ok(suggestions)
     }
 
     fn get_completion(
@@ -60,4 +194,4 @@ impl Autocomplete for IssueCompleter {
             None => Replacement::None
         })
     }
-}
\ No newline at end of file
+}
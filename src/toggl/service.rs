@@ -1,27 +1,36 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::BTreeMap;
 
-use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
 use reqwest::{header::CONTENT_TYPE, Client, Method};
+use serde_json::json;
 
-use super::structs::{EntryTag, MergedEntry, TagRequest, TimeEntry};
+use crate::storage::metadata::{retreive_metadata, store_metadata};
 
-const TIME_URL: &str = "https://api.track.toggl.com/api/v9/me/time_entries";
+use super::structs::{
+    BulkEditFailure, BulkEditResponse, EntryTag, MergedEntry, NewTimeEntry, PatchOperation,
+    Project, SummaryGroup, SummaryRequest, SummaryResponse, Tag, TimeEntry, TogglClient,
+    TogglMetadata, Workspace,
+};
+
+const MAX_BULK_IDS: usize = 100;
 
 pub async fn retrieve_entries(
     client: &Client,
+    base_url: &str,
     username: &str,
     password: &str,
     start_date: NaiveDate,
+    end_date: NaiveDate,
 ) -> anyhow::Result<Vec<TimeEntry>> {
     let start_string = start_date.format("%Y-%m-%d").to_string();
-    let end_string = start_date
+    let end_string = end_date
         .checked_add_days(Days::new(1))
         .expect("Should never overflow?")
         .format("%Y-%m-%d")
         .to_string();
 
     let available_entries = client
-        .request(Method::GET, TIME_URL)
+        .request(Method::GET, format!("{}/me/time_entries", base_url))
         .query(&[("start_date", start_string), ("end_date", end_string)])
         .header(CONTENT_TYPE, "application/json")
         .basic_auth(username, Some(password))
@@ -33,28 +42,210 @@ pub async fn retrieve_entries(
     Ok(available_entries)
 }
 
+pub async fn create_entry(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+    entry: &NewTimeEntry,
+) -> anyhow::Result<TimeEntry> {
+    let created = client
+        .request(
+            Method::POST,
+            format!("{}/workspaces/{}/time_entries", base_url, entry.workspace_id),
+        )
+        .header(CONTENT_TYPE, "application/json")
+        .basic_auth(username, Some(password))
+        .json(entry)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TimeEntry>()
+        .await?;
+    Ok(created)
+}
+
+pub async fn retrieve_current_entry(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<Option<TimeEntry>> {
+    let current = client
+        .request(Method::GET, format!("{}/me/time_entries/current", base_url))
+        .basic_auth(username, Some(password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Option<TimeEntry>>()
+        .await?;
+    Ok(current)
+}
+
+pub async fn stop_entry(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+    entry: &TimeEntry,
+) -> anyhow::Result<TimeEntry> {
+    let stopped = client
+        .request(
+            Method::PATCH,
+            format!(
+                "{}/workspaces/{}/time_entries/{}/stop",
+                base_url, entry.workspace_id, entry.id
+            ),
+        )
+        .basic_auth(username, Some(password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TimeEntry>()
+        .await?;
+    Ok(stopped)
+}
+
+// Totals per project and description from the reports API, the end date is inclusive
+pub async fn retrieve_summary(
+    client: &Client,
+    reports_url: &str,
+    username: &str,
+    password: &str,
+    workspace_id: i64,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> anyhow::Result<Vec<SummaryGroup>> {
+    let request = SummaryRequest {
+        start_date: start_date.format("%Y-%m-%d").to_string(),
+        end_date: end_date.format("%Y-%m-%d").to_string(),
+        grouping: "projects".to_string(),
+        sub_grouping: "time_entries".to_string(),
+    };
+    let summary = client
+        .request(
+            Method::POST,
+            format!("{}/workspace/{}/summary/time_entries", reports_url, workspace_id),
+        )
+        .header(CONTENT_TYPE, "application/json")
+        .basic_auth(username, Some(password))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<SummaryResponse>()
+        .await?;
+    Ok(summary.groups)
+}
+
+pub async fn retrieve_workspaces(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<Vec<Workspace>> {
+    let workspaces = client
+        .request(Method::GET, format!("{}/me/workspaces", base_url))
+        .basic_auth(username, Some(password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<Workspace>>()
+        .await?;
+    Ok(workspaces)
+}
+
+pub async fn retrieve_metadata(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<TogglMetadata> {
+    let projects = client
+        .request(Method::GET, format!("{}/me/projects", base_url))
+        .basic_auth(username, Some(password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<Project>>()
+        .await?;
+    let clients = client
+        .request(Method::GET, format!("{}/me/clients", base_url))
+        .basic_auth(username, Some(password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Option<Vec<TogglClient>>>()
+        .await?;
+    let tags = client
+        .request(Method::GET, format!("{}/me/tags", base_url))
+        .basic_auth(username, Some(password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Option<Vec<Tag>>>()
+        .await?;
+
+    Ok(TogglMetadata {
+        fetched_at: Utc::now().timestamp(),
+        projects,
+        clients: clients.unwrap_or_default(),
+        tags: tags.unwrap_or_default(),
+    })
+}
+
+pub async fn cached_metadata(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+    refresh_hours: u64,
+) -> TogglMetadata {
+    let cached = retreive_metadata();
+    if let Some(metadata) = cached.as_ref() && !metadata.is_stale(refresh_hours) {
+        return cached.unwrap_or_default();
+    }
+    match retrieve_metadata(client, base_url, username, password).await {
+        Ok(metadata) => {
+            if store_metadata(&metadata).is_err() {
+                println!("Failed to cache Toggl projects and tags :(");
+            }
+            metadata
+        }
+        Err(_) => {
+            println!("Failed to retrieve Toggl projects, using the cached ones");
+            cached.unwrap_or_default()
+        }
+    }
+}
+
 pub fn merge_filter_entries(entries: Vec<TimeEntry>) -> Vec<MergedEntry> {
-    let grouped_entries: BTreeMap<String, Vec<TimeEntry>> =
+    let grouped_entries: BTreeMap<(Option<NaiveDate>, String), Vec<TimeEntry>> =
         entries.into_iter().fold(BTreeMap::new(), |mut acc, entry| {
             //Filter out deleted and non-finished entries
             if entry.duration.is_positive() && entry.server_deleted_at.is_none() {
-                //Merge on description (TODO: Maybe also merge on projects/tags etc, just like toggl)
-                acc.entry(entry.description.to_string())
+                //Merge on the local day and description (TODO: Maybe also merge on projects/tags etc, just like toggl)
+                let day = entry.start.map(|start| start.with_timezone(&Local).date_naive());
+                acc.entry((day, entry.description.to_string()))
                     .or_default()
                     .push(entry);
             }
             acc
         });
     let mut merged_entries: Vec<MergedEntry> = Vec::new();
-    for (description, group) in grouped_entries {
+    for ((_, description), group) in grouped_entries {
         let first = group.get(0);
         if let Some(first_entry) = first {
             let group_len = group.len();
             let mut tags: Vec<_> = Vec::with_capacity(group_len);
             let mut start_time: DateTime<Utc> = Utc::now();
             let mut duration = 0i64;
+            let mut billable_duration = 0i64;
             for entry in group.iter() {
                 duration += entry.duration;
+                if entry.billable {
+                    billable_duration += entry.duration;
+                }
                 tags.push(EntryTag {
                     id: entry.id,
                     user_id: entry.user_id,
@@ -65,10 +256,16 @@ pub fn merge_filter_entries(entries: Vec<TimeEntry>) -> Vec<MergedEntry> {
                     start_time = start_time.min(start.with_timezone(&Utc))
                 }
             }
+            //Only label the merged entry with a project when all of its entries share it
+            let project_id = first_entry
+                .project_id
+                .filter(|project_id| group.iter().all(|entry| entry.project_id == Some(*project_id)));
             let merged = MergedEntry {
                 user_id: first_entry.user_id,
                 workspace_id: first_entry.workspace_id,
+                project_id,
                 duration,
+                billable_duration,
                 description,
                 start: start_time,
                 tags,
@@ -82,42 +279,86 @@ pub fn merge_filter_entries(entries: Vec<TimeEntry>) -> Vec<MergedEntry> {
     merged_entries
 }
 
-fn add_tag(entry: &EntryTag, new_tag: &str) -> TagRequest {
-    if let Some(existing_tags) = &entry.tags {
-        let mut tags = HashSet::new();
-        tags.insert(new_tag.to_string());
-        for tag in existing_tags.iter() {
-            tags.insert(tag.to_string());
-        }
-        TagRequest { tags }
-    } else {
-        let mut tags = HashSet::new();
-        tags.insert(new_tag.to_string());
-        TagRequest { tags }
-    }
+pub async fn tag_entries(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+    workspace_id: i64,
+    entry_ids: &[i64],
+    new_tag: &str,
+) -> anyhow::Result<BulkEditResponse> {
+    let operations = vec![PatchOperation {
+        op: "add".to_string(),
+        path: "/tags".to_string(),
+        value: json!([new_tag]),
+    }];
+    bulk_edit_entries(client, base_url, username, password, workspace_id, entry_ids, &operations).await
 }
 
-pub async fn tag_entry(
+pub async fn describe_entries(
     client: &Client,
+    base_url: &str,
     username: &str,
     password: &str,
-    entry: EntryTag,
-    new_tag: &str,
-) -> anyhow::Result<()> {
-    let request = add_tag(&entry, new_tag);
-    //Handle?
-    let _ = client
-        .request(
-            Method::PUT,
-            format!(
-                "https://api.track.toggl.com/api/v9/workspaces/{}/time_entries/{}",
-                entry.workspace_id, entry.id
-            ),
-        )
-        .header(CONTENT_TYPE, "application/json")
-        .basic_auth(username, Some(password))
-        .json(&request)
-        .send()
-        .await?;
-    Ok(())
+    workspace_id: i64,
+    entry_ids: &[i64],
+    description: &str,
+) -> anyhow::Result<BulkEditResponse> {
+    let operations = vec![PatchOperation {
+        op: "replace".to_string(),
+        path: "/description".to_string(),
+        value: json!(description),
+    }];
+    bulk_edit_entries(client, base_url, username, password, workspace_id, entry_ids, &operations).await
+}
+
+async fn bulk_edit_entries(
+    client: &Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+    workspace_id: i64,
+    entry_ids: &[i64],
+    operations: &[PatchOperation],
+) -> anyhow::Result<BulkEditResponse> {
+    let mut result = BulkEditResponse {
+        success: Vec::new(),
+        failure: Vec::new(),
+    };
+    for chunk in entry_ids.chunks(MAX_BULK_IDS) {
+        let ids = chunk
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let response = async {
+            client
+                .request(
+                    Method::PATCH,
+                    format!("{}/workspaces/{}/time_entries/{}", base_url, workspace_id, ids),
+                )
+                .header(CONTENT_TYPE, "application/json")
+                .basic_auth(username, Some(password))
+                .json(operations)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<BulkEditResponse>()
+                .await
+        }
+        .await;
+        match response {
+            Ok(response) => {
+                result.success.extend(response.success);
+                result.failure.extend(response.failure);
+            }
+            //Keep going, the other chunks may still succeed
+            Err(error) => result.failure.extend(chunk.iter().map(|id| BulkEditFailure {
+                id: *id,
+                message: error.to_string(),
+            })),
+        }
+    }
+    Ok(result)
 }
@@ -1,16 +1,22 @@
 use std::collections::HashSet;
 
 use chrono::{DateTime, FixedOffset, Utc};
+use savefile_derive::Savefile;
 use serde::{Deserialize, Deserializer, Serialize};
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct TimeEntry {
     pub id: i64,
     pub user_id: i64,
     pub workspace_id: i64,
+    pub project_id: Option<i64>,
+    #[serde(default)]
     pub at: String,
     pub description: String,
     pub duration: i64,
+    #[serde(default)]
     pub duronly: bool,
+    #[serde(default)]
+    pub billable: bool,
     #[serde(deserialize_with = "date_time_from_str")]
     pub start: Option<DateTime<FixedOffset>>,
     pub stop: Option<String>,
@@ -21,7 +27,9 @@ pub struct TimeEntry {
 pub struct MergedEntry {
     pub user_id: i64,
     pub workspace_id: i64,
+    pub project_id: Option<i64>,
     pub duration: i64,
+    pub billable_duration: i64,
     pub description: String,
     pub start: DateTime<Utc>,
     pub tags: Vec<EntryTag>,
@@ -48,6 +56,117 @@ where
 }
 
 #[derive(Serialize, Debug)]
-pub struct TagRequest {
-    pub tags: HashSet<String>,
+pub struct NewTimeEntry {
+    pub created_with: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub start: String,
+    pub duration: i64,
+    pub workspace_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PatchOperation {
+    pub op: String,
+    pub path: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BulkEditFailure {
+    pub id: i64,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BulkEditResponse {
+    pub success: Vec<i64>,
+    pub failure: Vec<BulkEditFailure>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SummaryRequest {
+    pub start_date: String,
+    pub end_date: String,
+    pub grouping: String,
+    pub sub_grouping: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SummarySubGroup {
+    pub title: Option<String>,
+    pub seconds: u64,
+}
+
+// Grouped by project, the id is None for entries without one
+#[derive(Deserialize, Debug)]
+pub struct SummaryGroup {
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub sub_groups: Vec<SummarySubGroup>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SummaryResponse {
+    #[serde(default)]
+    pub groups: Vec<SummaryGroup>,
+}
+
+
+#[derive(Deserialize, Debug)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Deserialize, Savefile, Debug, Clone)]
+pub struct Project {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub client_id: Option<i64>,
+    pub name: String,
+}
+
+#[derive(Deserialize, Savefile, Debug, Clone)]
+pub struct TogglClient {
+    pub id: i64,
+    pub wid: i64,
+    pub name: String,
+}
+
+#[derive(Deserialize, Savefile, Debug, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub name: String,
 }
+
+#[derive(Savefile, Debug, Default)]
+pub struct TogglMetadata {
+    pub fetched_at: i64,
+    pub projects: Vec<Project>,
+    pub clients: Vec<TogglClient>,
+    #[savefile_versions = "1.."]
+    pub tags: Vec<Tag>,
+}
+
+impl TogglMetadata {
+    pub fn is_stale(&self, refresh_hours: u64) -> bool {
+        let age = Utc::now().timestamp() - self.fetched_at;
+        age < 0 || age as u64 >= refresh_hours * 60 * 60
+    }
+
+    pub fn project_label(&self, project_id: Option<i64>) -> Option<String> {
+        let project = self.projects.iter().find(|p| Some(p.id) == project_id)?;
+        let client = self
+            .clients
+            .iter()
+            .find(|c| Some(c.id) == project.client_id);
+        match client {
+            Some(client) => Some(format!("{} / {}", project.name, client.name)),
+            None => Some(project.name.to_string()),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use colored::Colorize;
+use reqwest::Client;
+
+use crate::{
+    jira::service::{get_issue, get_transitions, transition_issue, JiraSite},
+    storage::config::{Config, TransitionRule},
+};
+
+pub struct Transitioned {
+    pub key: String,
+    pub from: String,
+    pub to: String,
+}
+
+// Issues still in the "from" status of their project's rule move on once time is logged on them
+pub async fn transition_logged_issues(
+    client: &Client,
+    site: &JiraSite,
+    config: &Config,
+    keys: &[String],
+) -> Vec<Transitioned> {
+    let mut transitioned = Vec::new();
+    for key in keys {
+        let Some(rule) = config.transition_rule(key) else {
+            continue;
+        };
+        match apply_rule(client, site, key, rule).await {
+            Ok(true) => transitioned.push(Transitioned {
+                key: key.to_string(),
+                from: rule.from.to_string(),
+                to: rule.to.to_string(),
+            }),
+            Ok(false) => {}
+            Err(error) => println!("{} {}: {}", "Failed to transition".red(), key, error),
+        }
+    }
+    transitioned
+}
+
+async fn apply_rule(
+    client: &Client,
+    site: &JiraSite,
+    key: &str,
+    rule: &TransitionRule,
+) -> anyhow::Result<bool> {
+    let Some(issue) = get_issue(client, site, key, &["status"]).await? else {
+        return Ok(false);
+    };
+    let in_from_status = issue
+        .fields
+        .status
+        .is_some_and(|status| status.name.eq_ignore_ascii_case(&rule.from));
+    if !in_from_status {
+        return Ok(false);
+    }
+    let transitions = get_transitions(client, site, key).await?;
+    let Some(transition) = transitions
+        .iter()
+        .find(|transition| transition.to.name.eq_ignore_ascii_case(&rule.to))
+    else {
+        anyhow::bail!("no transition leads to {}", rule.to);
+    };
+    transition_issue(client, site, key, &transition.id).await?;
+    Ok(true)
+}
+
+pub fn print_transitioned(transitioned: &[Transitioned]) {
+    if transitioned.is_empty() {
+        return;
+    }
+    println!("Transitioned in Jira:");
+    for issue in transitioned {
+        println!("{}: {} -> {}", issue.key.blue(), issue.from, issue.to.green());
+    }
+}
@@ -1,3 +1,66 @@
+use std::ops::Range;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc, Weekday};
+use inquire::DateSelect;
+use regex::{Regex, RegexBuilder};
+
+pub const DEFAULT_KEY_PATTERN: &str = r"\b[A-Z][A-Z0-9_]+-[1-9][0-9]*";
+
+// Finds issue keys in tags and descriptions, lowercase keys only count for the allowed projects
+#[derive(Clone, Debug)]
+pub struct KeyMatcher {
+    pattern: Regex,
+    projects: Option<Vec<String>>,
+}
+
+impl KeyMatcher {
+    pub fn new(pattern: &str, projects: Option<&[String]>) -> anyhow::Result<KeyMatcher> {
+        let pattern = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+        Ok(KeyMatcher {
+            pattern,
+            projects: projects.map(|projects| projects.iter().map(|project| project.to_uppercase()).collect()),
+        })
+    }
+
+    pub fn find(&self, input: &str) -> Option<String> {
+        self.find_at(input).map(|(_, key)| key)
+    }
+
+    // The whole input is one allowed key
+    pub fn is_key(&self, input: &str) -> bool {
+        self.find(input).as_deref() == Some(input)
+    }
+
+    // The input without its key
+    pub fn remove(&self, input: &str) -> String {
+        match self.find_at(input) {
+            Some((range, _)) => format!("{}{}", &input[..range.start], &input[range.end..]),
+            None => input.to_string(),
+        }
+    }
+
+    fn find_at(&self, input: &str) -> Option<(Range<usize>, String)> {
+        self.pattern.find_iter(input).find_map(|found| {
+            let key = found.as_str().to_uppercase();
+            self.allows(found.as_str(), &key).then(|| (found.range(), key))
+        })
+    }
+
+    fn allows(&self, found: &str, key: &str) -> bool {
+        let project = key.split('-').next().unwrap_or_default();
+        match &self.projects {
+            Some(projects) => projects.iter().any(|allowed| allowed == project),
+            None => found == key,
+        }
+    }
+}
+
+impl Default for KeyMatcher {
+    fn default() -> Self {
+        KeyMatcher::new(DEFAULT_KEY_PATTERN, None).unwrap()
+    }
+}
+
 pub fn clean_description(input: &str) -> String {
     let chars: &[_] = &[':', '-'];
     input.trim().trim_matches(chars).trim().to_string()
@@ -5,7 +68,52 @@ pub fn clean_description(input: &str) -> String {
 
 pub fn clean_key(input: &str) -> String {
     match input.split_once(':') {
-        Some((key, _)) => key.trim().to_string(),
-        None => input.trim().to_string(),
+        Some((key, _)) => key.trim().to_uppercase(),
+        None => input.trim().to_uppercase(),
+    }
+}
+
+// Levenshtein distance, to suggest keys close to a mistyped one
+pub fn key_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+pub fn week_bounds(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    (monday, monday + Duration::days(6))
+}
+
+pub fn month_bounds(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = date.with_day(1).unwrap_or(date);
+    let next_month = first.checked_add_months(Months::new(1)).unwrap_or(first);
+    (first, next_month - Duration::days(1))
+}
+
+pub fn select_week(week: bool) -> anyhow::Result<NaiveDate> {
+    if week {
+        return Ok(Utc::now().date_naive());
+    }
+    let selected_date = DateSelect::new("Which week?")
+        .with_starting_date(Utc::now().date_naive())
+        .with_week_start(Weekday::Mon)
+        .prompt()?;
+    Ok(selected_date)
+}
+
+pub fn format_hours(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    match minutes % 60 {
+        0 => format!("{}h", minutes / 60),
+        rest => format!("{}h{:02}", minutes / 60, rest),
     }
 }
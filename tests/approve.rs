@@ -0,0 +1,136 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{credentials, use_temp_dir, MockResponse, MockServer};
+use serde_json::{json, Value};
+use toggl_sync::{
+    approve::{show_approval, submit_timesheet},
+    prompts::{remaining_answers, script_answers},
+    storage::config::{Config, JiraFlavour},
+};
+use tokio::sync::Mutex;
+
+// Scripted answers are shared by the whole test binary
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+fn approval(status: &str) -> Value {
+    json!({
+        "period": { "from": "2026-10-12", "to": "2026-10-18" },
+        "requiredSeconds": 144000,
+        "timeSpentSeconds": 144000,
+        "status": { "key": status, "comment": null },
+        "reviewer": null
+    })
+}
+
+async fn setup(name: &str, status: &str) -> (MockServer, Config) {
+    use_temp_dir(name);
+    let tempo = MockServer::start().await;
+    tempo.mock(
+        "GET",
+        "/timesheet-approvals/user/account-1",
+        vec![MockResponse::json(200, approval(status))],
+    );
+    let config = Config {
+        tempo_url: Some(tempo.url.to_string()),
+        ..Default::default()
+    };
+    (tempo, config)
+}
+
+#[tokio::test]
+async fn submits_the_week_to_the_selected_reviewer() {
+    let _serial = SERIAL.lock().await;
+    let (tempo, config) = setup("approve_submit", "OPEN").await;
+    tempo.mock(
+        "GET",
+        "/timesheet-approvals/user/account-1/reviewers",
+        vec![MockResponse::json(
+            200,
+            json!({ "results": [
+                { "accountId": "reviewer-1", "displayName": "Alice" },
+                { "accountId": "reviewer-2", "displayName": "Bob" }
+            ] }),
+        )],
+    );
+    tempo.mock(
+        "POST",
+        "/timesheet-approvals/user/account-1/submit",
+        vec![MockResponse::json(200, approval("WAITING_FOR_APPROVAL"))],
+    );
+
+    script_answers(&["Bob", "y", "Week of the release"]);
+    let date = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    submit_timesheet(&credentials(), &config, date).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let submitted = tempo.requests("POST", "/timesheet-approvals/user/account-1/submit");
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].query, "from=2026-10-12&to=2026-10-18");
+    assert_eq!(submitted[0].header("authorization"), Some("Bearer tempo-token"));
+    assert_eq!(
+        submitted[0].json(),
+        json!({ "comment": "Week of the release", "reviewerAccountId": "reviewer-2" })
+    );
+}
+
+#[tokio::test]
+async fn submits_without_a_comment_when_skipped() {
+    let _serial = SERIAL.lock().await;
+    let (tempo, config) = setup("approve_skip", "OPEN").await;
+    tempo.mock(
+        "GET",
+        "/timesheet-approvals/user/account-1/reviewers",
+        vec![MockResponse::json(200, json!({ "results": [{ "accountId": "reviewer-1", "displayName": "Alice" }] }))],
+    );
+    tempo.mock(
+        "POST",
+        "/timesheet-approvals/user/account-1/submit",
+        vec![MockResponse::json(200, approval("WAITING_FOR_APPROVAL"))],
+    );
+
+    script_answers(&["y", ""]);
+    let date = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    submit_timesheet(&credentials(), &config, date).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let submitted = tempo.requests("POST", "/timesheet-approvals/user/account-1/submit");
+    assert_eq!(submitted[0].json(), json!({ "reviewerAccountId": "reviewer-1" }));
+}
+
+#[tokio::test]
+async fn does_not_submit_a_week_that_is_not_open() {
+    let _serial = SERIAL.lock().await;
+    let (tempo, config) = setup("approve_closed", "WAITING_FOR_APPROVAL").await;
+
+    script_answers(&[]);
+    let date = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    submit_timesheet(&credentials(), &config, date).await.unwrap();
+
+    assert!(tempo.requests("GET", "/timesheet-approvals/user/account-1/reviewers").is_empty());
+    assert!(tempo.requests("POST", "/timesheet-approvals/user/account-1/submit").is_empty());
+}
+
+#[tokio::test]
+async fn shows_the_status_of_the_week() {
+    let _serial = SERIAL.lock().await;
+    let (tempo, config) = setup("approve_status", "APPROVED").await;
+
+    let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    show_approval(&credentials(), &config, date).await.unwrap();
+
+    let requested = tempo.requests("GET", "/timesheet-approvals/user/account-1");
+    assert_eq!(requested.len(), 1);
+    assert_eq!(requested[0].query, "from=2026-10-12&to=2026-10-18");
+}
+
+#[tokio::test]
+async fn refuses_approvals_on_jira_server() {
+    let _serial = SERIAL.lock().await;
+    let (tempo, mut config) = setup("approve_server", "OPEN").await;
+    config.jira_flavour = Some(JiraFlavour::Server);
+
+    let date = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    assert!(show_approval(&credentials(), &config, date).await.is_err());
+    assert!(tempo.requests("GET", "/timesheet-approvals/user/account-1").is_empty());
+}
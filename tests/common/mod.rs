@@ -0,0 +1,299 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use toggl_sync::storage::credentials::Credentials;
+
+// Stored files are written to the working directory, keep every test in its own
+pub fn use_temp_dir(name: &str) {
+    let dir = std::env::temp_dir().join(format!("toggl_sync_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_current_dir(&dir).unwrap();
+}
+
+pub fn time_entry(id: i64, description: &str, start: &str, duration: i64, billable: bool, tags: Value) -> Value {
+    json!({
+        "id": id,
+        "user_id": 7,
+        "workspace_id": 1,
+        "project_id": 5,
+        "at": start,
+        "description": description,
+        "duration": duration,
+        "duronly": false,
+        "billable": billable,
+        "start": start,
+        "stop": null,
+        "server_deleted_at": null,
+        "tags": tags,
+    })
+}
+
+pub fn credentials() -> Credentials {
+    Credentials {
+        username: "toggl-user".to_string(),
+        password: "toggl-password".to_string(),
+        tempo_token: "tempo-token".to_string(),
+        account_id: "account-1".to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn jira_credentials() -> Credentials {
+    Credentials {
+        jira_email: "me@example.com".to_string(),
+        jira_token: "jira-token".to_string(),
+        ..credentials()
+    }
+}
+
+// Toggl with the given entries and its metadata, Tempo with a schedule and accepting worklogs
+pub async fn mock_toggl_and_tempo(entries: Value) -> (MockServer, MockServer) {
+    let toggl = MockServer::start().await;
+    toggl.mock("GET", "/me/time_entries", vec![MockResponse::json(200, entries)]);
+    toggl.mock(
+        "GET",
+        "/me/projects",
+        vec![MockResponse::json(
+            200,
+            json!([{ "id": 5, "workspace_id": 1, "client_id": 9, "name": "Platform" }]),
+        )],
+    );
+    toggl.mock(
+        "GET",
+        "/me/clients",
+        vec![MockResponse::json(200, json!([{ "id": 9, "wid": 1, "name": "Acme" }]))],
+    );
+    toggl.mock(
+        "GET",
+        "/me/tags",
+        vec![MockResponse::json(200, json!([{ "id": 3, "workspace_id": 1, "name": "PROJ-12" }]))],
+    );
+    let tempo = MockServer::start().await;
+    tempo.mock(
+        "GET",
+        "/user-schedule/account-1",
+        vec![MockResponse::json(
+            200,
+            json!({ "results": [{ "date": "2026-10-12", "requiredSeconds": 28800, "type": "WORKING_DAY" }] }),
+        )],
+    );
+    tempo.mock("POST", "/worklogs", vec![MockResponse::json(200, json!({}))]);
+    (toggl, tempo)
+}
+
+// Answers a bulk edit of the given comma separated entry ids in workspace 1
+pub fn mock_bulk_edit(toggl: &MockServer, ids: &str, success: Value, failure: Value) {
+    toggl.mock(
+        "PATCH",
+        &format!("/workspaces/1/time_entries/{}", ids),
+        vec![MockResponse::json(200, json!({ "success": success, "failure": failure }))],
+    );
+}
+
+pub fn mock_jira_permission(jira: &MockServer) {
+    jira.mock(
+        "GET",
+        "/rest/api/3/mypermissions",
+        vec![MockResponse::json(200, json!({ "permissions": { "WORK_ON_ISSUES": { "havePermission": true } } }))],
+    );
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("Request body should be json")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub delay: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> MockResponse {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+            delay: None,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> MockResponse {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> MockResponse {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+struct Route {
+    method: String,
+    path: String,
+    // Responses are used in order, the last one is repeated
+    responses: VecDeque<MockResponse>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    requests: Vec<RecordedRequest>,
+}
+
+// Minimal HTTP/1.1 stand-in for the Toggl and Tempo APIs, one request per connection
+pub struct MockServer {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub async fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state: Arc<Mutex<State>> = Arc::default();
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, state).await;
+                });
+            }
+        });
+        MockServer { url, state }
+    }
+
+    // Mocking a route again replaces its earlier responses
+    pub fn mock(&self, method: &str, path: &str, responses: Vec<MockResponse>) {
+        let mut state = self.state.lock().unwrap();
+        state.routes.retain(|route| route.method != method || route.path != path);
+        state.routes.push(Route {
+            method: method.to_string(),
+            path: path.to_string(),
+            responses: responses.into(),
+        });
+    }
+
+    pub fn requests(&self, method: &str, path: &str) -> Vec<RecordedRequest> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|request| request.method == method && request.path == path)
+            .cloned()
+            .collect()
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<State>>) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), query.to_string()),
+        None => (target, String::new()),
+    };
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect::<Vec<_>>();
+    let content_length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buffer.len() < header_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body = String::from_utf8_lossy(&buffer[header_end..]).to_string();
+
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            query,
+            headers,
+            body,
+        });
+        state
+            .routes
+            .iter_mut()
+            .find(|route| route.method == method && route.path == path)
+            .and_then(|route| {
+                if route.responses.len() > 1 {
+                    route.responses.pop_front()
+                } else {
+                    route.responses.front().cloned()
+                }
+            })
+            .unwrap_or(MockResponse::json(404, json!({ "message": "Not mocked" })))
+    };
+
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+    let mut raw = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (key, value) in response.headers.iter() {
+        raw.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    raw.push_str("\r\n");
+    raw.push_str(&response.body);
+    stream.write_all(raw.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{jira_credentials, MockResponse, MockServer};
+use serde_json::json;
+use toggl_sync::{
+    prompts::{remaining_answers, script_answers},
+    storage::{
+        config::Config,
+        credentials::{discover_account_id, Credentials},
+    },
+};
+use tokio::sync::Mutex;
+
+// The scripted answers are process wide
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+async fn setup() -> (MockServer, Config, Credentials) {
+    let jira = MockServer::start().await;
+    jira.mock(
+        "GET",
+        "/rest/api/3/myself",
+        vec![MockResponse::json(
+            200,
+            json!({
+                "accountId": "5b10ac8d82e05b22cc7d4ef5",
+                "displayName": "Mia Krystof",
+                "emailAddress": "me@example.com"
+            }),
+        )],
+    );
+    let config = Config {
+        jira_url: Some(jira.url.to_string()),
+        ..Default::default()
+    };
+    let credentials = jira_credentials();
+    (jira, config, credentials)
+}
+
+#[tokio::test]
+async fn fills_the_account_id_from_jira() {
+    let _serial = SERIAL.lock().await;
+    let (jira, config, mut credentials) = setup().await;
+
+    script_answers(&["y"]);
+    let discovered = discover_account_id(&mut credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    assert!(discovered);
+    assert_eq!(credentials.account_id, "5b10ac8d82e05b22cc7d4ef5");
+    let requests = jira.requests("GET", "/rest/api/3/myself");
+    assert_eq!(requests.len(), 1);
+    // me@example.com:jira-token
+    assert_eq!(
+        requests[0].header("authorization"),
+        Some("Basic bWVAZXhhbXBsZS5jb206amlyYS10b2tlbg==")
+    );
+}
+
+#[tokio::test]
+async fn keeps_the_account_id_when_the_name_is_wrong() {
+    let _serial = SERIAL.lock().await;
+    let (_jira, config, mut credentials) = setup().await;
+    let account_id = credentials.account_id.to_string();
+
+    script_answers(&["n"]);
+    let discovered = discover_account_id(&mut credentials, &config).await.unwrap();
+
+    assert!(!discovered);
+    assert_eq!(credentials.account_id, account_id);
+}
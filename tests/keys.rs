@@ -0,0 +1,171 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use common::{jira_credentials, use_temp_dir, MockResponse, MockServer};
+use inquire::{autocompletion::Replacement, Autocomplete};
+use serde_json::json;
+use toggl_sync::{
+    storage::{config::Config, keys::run_import},
+    toggl::issue_completer::IssueCompleter,
+};
+
+#[tokio::test]
+async fn imports_keys_with_the_configured_jql() {
+    use_temp_dir("import_keys");
+    let jira = MockServer::start().await;
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![
+            MockResponse::json(
+                200,
+                json!({
+                    "issues": [{ "key": "PROJ-1", "fields": { "summary": "Meetings" } }],
+                    "nextPageToken": "page-2"
+                }),
+            ),
+            MockResponse::json(
+                200,
+                json!({ "issues": [{ "key": "PROJ-2", "fields": { "summary": "Support" } }] }),
+            ),
+        ],
+    );
+    let config = Config {
+        jira_url: Some(jira.url.to_string()),
+        import_jql: Some("project = PROJ".to_string()),
+        ..Default::default()
+    };
+    let credentials = jira_credentials();
+
+    run_import(&credentials, &config, false).await.unwrap();
+
+    let searched = jira.requests("POST", "/rest/api/3/search/jql");
+    assert_eq!(searched.len(), 2);
+    assert_eq!(searched[0].json()["jql"], "project = PROJ");
+    assert_eq!(searched[0].json()["fields"], json!(["summary"]));
+    assert_eq!(searched[1].json()["nextPageToken"], "page-2");
+    // me@example.com:jira-token
+    assert_eq!(
+        searched[0].header("authorization"),
+        Some("Basic bWVAZXhhbXBsZS5jb206amlyYS10b2tlbg==")
+    );
+}
+
+fn deploy_issues() -> MockResponse {
+    MockResponse::json(
+        200,
+        json!({
+            "issues": [
+                { "key": "PROJ-1", "fields": { "summary": "Deploy pipeline" } },
+                { "key": "PROJ-7", "fields": { "summary": "Deploy docs" } }
+            ]
+        }),
+    )
+}
+
+fn completer(jira: &MockServer) -> IssueCompleter {
+    let config = Config {
+        jira_url: Some(jira.url.to_string()),
+        ..Default::default()
+    };
+    let credentials = jira_credentials();
+    let known = HashMap::from([("PROJ-1".to_string(), "Deploy pipeline".to_string())]);
+    IssueCompleter::new(known).with_jira(config.jira_site(&credentials))
+}
+
+// Long enough for typing to pause and the mock Jira to answer
+const SEARCHED: Duration = Duration::from_millis(700);
+
+#[tokio::test]
+async fn suggests_jira_issues_when_few_keys_match() {
+    let jira = MockServer::start().await;
+    jira.mock("POST", "/rest/api/3/search/jql", vec![deploy_issues()]);
+    let mut completer = completer(&jira);
+
+    let searching = completer.get_suggestions("deploy").unwrap();
+    assert_eq!(searching.len(), 2);
+    assert!(searching[1].contains("Searching Jira..."));
+
+    tokio::time::sleep(SEARCHED).await;
+    let suggestions = completer.get_suggestions("deploy").unwrap();
+    assert_eq!(suggestions.len(), 2);
+    assert!(suggestions[0].starts_with("PROJ-1: "));
+    assert!(suggestions[1].starts_with("PROJ-7: ") && suggestions[1].contains("(Jira)"));
+    let searched = jira.requests("POST", "/rest/api/3/search/jql");
+    assert_eq!(searched.len(), 1);
+    assert_eq!(searched[0].json()["jql"], "summary ~ \"deploy*\" ORDER BY updated DESC");
+    assert_eq!(completer.get_suggestions("de").unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn searches_once_typing_pauses() {
+    let jira = MockServer::start().await;
+    jira.mock("POST", "/rest/api/3/search/jql", vec![deploy_issues()]);
+    let mut completer = completer(&jira);
+
+    for input in ["dep", "depl", "deplo", "deploy"] {
+        completer.get_suggestions(input).unwrap();
+    }
+    tokio::time::sleep(SEARCHED).await;
+    let searched = jira.requests("POST", "/rest/api/3/search/jql");
+    assert_eq!(searched.len(), 1);
+    assert_eq!(searched[0].json()["jql"], "summary ~ \"deploy*\" ORDER BY updated DESC");
+
+    // Until the next search is done the earlier results are narrowed down
+    let narrowed = completer.get_suggestions("deploy d").unwrap();
+    assert_eq!(narrowed.len(), 2);
+    assert!(narrowed[0].starts_with("PROJ-7: "));
+    assert!(narrowed[1].contains("Searching Jira..."));
+    tokio::time::sleep(SEARCHED).await;
+    assert_eq!(jira.requests("POST", "/rest/api/3/search/jql").len(), 2);
+
+    // A query skipped while typing is searched when typed again
+    completer.get_suggestions("deplo").unwrap();
+    tokio::time::sleep(SEARCHED).await;
+    assert_eq!(jira.requests("POST", "/rest/api/3/search/jql").len(), 3);
+}
+
+#[tokio::test]
+async fn keeps_typing_while_jira_is_slow() {
+    let jira = MockServer::start().await;
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![deploy_issues().with_delay(Duration::from_millis(800))],
+    );
+    let mut completer = completer(&jira);
+
+    completer.get_suggestions("deploy").unwrap();
+    tokio::time::sleep(SEARCHED).await;
+    let started = Instant::now();
+    let suggestions = completer.get_suggestions("deploy").unwrap();
+    assert!(started.elapsed() < Duration::from_millis(100));
+    assert!(suggestions[1].contains("Searching Jira..."));
+
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    let suggestions = completer.get_suggestions("deploy").unwrap();
+    assert!(suggestions[1].starts_with("PROJ-7: ") && suggestions[1].contains("(Jira)"));
+    assert_eq!(jira.requests("POST", "/rest/api/3/search/jql").len(), 1);
+}
+
+#[tokio::test]
+async fn shows_that_the_jira_search_failed() {
+    let jira = MockServer::start().await;
+    jira.mock("POST", "/rest/api/3/search/jql", vec![MockResponse::json(500, json!({}))]);
+    let mut completer = completer(&jira);
+
+    completer.get_suggestions("deploy").unwrap();
+    tokio::time::sleep(SEARCHED).await;
+    let suggestions = completer.get_suggestions("deploy").unwrap();
+
+    assert_eq!(suggestions.len(), 2);
+    assert!(suggestions[1].contains("Jira search failed"));
+    assert_eq!(
+        completer.get_completion("deploy", Some(suggestions[1].to_string())).unwrap(),
+        Replacement::None
+    );
+}
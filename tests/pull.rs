@@ -0,0 +1,66 @@
+mod common;
+
+use common::{credentials, mock_toggl_and_tempo, time_entry, use_temp_dir, MockResponse};
+use serde_json::json;
+use toggl_sync::{
+    prompts::{remaining_answers, script_answers},
+    pull::run_pull,
+    storage::config::Config,
+};
+
+#[tokio::test]
+async fn creates_toggl_entries_for_uncovered_worklogs() {
+    use_temp_dir("pull");
+    let entries = json!([time_entry(1, "Fix login", "2026-10-12T09:00:00+00:00", 3600, true, json!(["PROJ-12"]))]);
+    let (toggl, tempo) = mock_toggl_and_tempo(entries).await;
+    toggl.mock(
+        "POST",
+        "/workspaces/1/time_entries",
+        vec![MockResponse::json(
+            200,
+            time_entry(2, "PROJ-7 Planning", "2026-10-12T13:00:00+00:00", 1800, false, json!(["PROJ-7"])),
+        )],
+    );
+    tempo.mock(
+        "GET",
+        "/worklogs/user/account-1",
+        vec![MockResponse::json(
+            200,
+            json!({ "results": [
+                {
+                    "issue": { "key": "PROJ-12" },
+                    "startDate": "2026-10-12",
+                    "startTime": "09:00:00",
+                    "timeSpentSeconds": 3600,
+                    "description": "Fix login"
+                },
+                {
+                    "issue": { "key": "PROJ-7" },
+                    "startDate": "2026-10-12",
+                    "startTime": "13:00:00",
+                    "timeSpentSeconds": 1800,
+                    "description": "Planning"
+                }
+            ] }),
+        )],
+    );
+    let config = Config {
+        toggl_url: Some(toggl.url.to_string()),
+        tempo_url: Some(tempo.url.to_string()),
+        workspace_ids: Some(vec![1]),
+        ..Default::default()
+    };
+
+    script_answers(&["2026-10-12", "2026-10-12", "y"]);
+    run_pull(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let created = toggl.requests("POST", "/workspaces/1/time_entries");
+    assert_eq!(created.len(), 1);
+    let entry = created[0].json();
+    assert_eq!(entry["description"], "PROJ-7 Planning");
+    assert_eq!(entry["tags"], json!(["PROJ-7"]));
+    assert_eq!(entry["start"], "2026-10-12T13:00:00+00:00");
+    assert_eq!(entry["duration"], 1800);
+    assert_eq!(entry["workspace_id"], 1);
+}
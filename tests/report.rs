@@ -0,0 +1,335 @@
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use common::{credentials, jira_credentials, use_temp_dir, MockResponse, MockServer};
+use serde_json::{json, Value};
+use toggl_sync::{
+    report::{epic_of, epic_totals, issue_totals, run_epic_report, run_report},
+    storage::{
+        config::{Config, JiraFlavour},
+        credentials::Credentials,
+        jira::{retreive_issue_parents, IssueParent},
+    },
+    tempo::structs::WorkLogResult,
+    toggl::structs::SummaryGroup,
+    utils::KeyMatcher,
+};
+use tokio::sync::Mutex;
+
+// Stored files are written to the working directory, which is process wide
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+fn summary() -> Value {
+    json!({
+        "groups": [
+            {
+                "id": 5,
+                "sub_groups": [
+                    { "id": null, "title": "PROJ-4 Planning", "seconds": 3600 },
+                    { "id": null, "title": "Fix login", "seconds": 5400 },
+                ]
+            },
+            { "id": null, "sub_groups": [{ "id": null, "title": "Lunch", "seconds": 1800 }] }
+        ]
+    })
+}
+
+fn worklogs() -> Value {
+    json!({
+        "results": [
+            {
+                "issue": { "key": "PROJ-12" },
+                "startDate": "2026-10-12",
+                "startTime": "09:00:00",
+                "timeSpentSeconds": 3600,
+                "description": "Fix login"
+            },
+            {
+                "issue": { "key": "PROJ-7" },
+                "startDate": "2026-10-13",
+                "startTime": "09:00:00",
+                "timeSpentSeconds": 900,
+                "description": "Review"
+            }
+        ]
+    })
+}
+
+#[tokio::test]
+async fn requests_a_summary_per_workspace() {
+    let _serial = SERIAL.lock().await;
+    use_temp_dir("report");
+    let toggl = MockServer::start().await;
+    toggl.mock("GET", "/me/projects", vec![MockResponse::json(200, json!([]))]);
+    toggl.mock("GET", "/me/clients", vec![MockResponse::json(200, json!(null))]);
+    toggl.mock("GET", "/me/tags", vec![MockResponse::json(200, json!(null))]);
+    toggl.mock(
+        "POST",
+        "/reports/workspace/1/summary/time_entries",
+        vec![MockResponse::json(200, summary())],
+    );
+    toggl.mock(
+        "POST",
+        "/reports/workspace/2/summary/time_entries",
+        vec![MockResponse::json(200, json!({ "groups": [] }))],
+    );
+    let tempo = MockServer::start().await;
+    tempo.mock("GET", "/worklogs/user/account-1", vec![MockResponse::json(200, worklogs())]);
+    let config = Config {
+        toggl_url: Some(toggl.url.to_string()),
+        tempo_url: Some(tempo.url.to_string()),
+        reports_url: Some(format!("{}/reports", toggl.url)),
+        workspace_ids: Some(vec![1, 2]),
+        ..Default::default()
+    };
+
+    let from = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+    let to = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    run_report(&credentials(), &config, from, to).await.unwrap();
+
+    let requested = toggl.requests("POST", "/reports/workspace/1/summary/time_entries");
+    assert_eq!(requested.len(), 1);
+    assert_eq!(
+        requested[0].json(),
+        json!({
+            "start_date": "2026-10-12",
+            "end_date": "2026-10-18",
+            "grouping": "projects",
+            "sub_grouping": "time_entries"
+        })
+    );
+    assert_eq!(toggl.requests("POST", "/reports/workspace/2/summary/time_entries").len(), 1);
+    assert!(toggl.requests("GET", "/me/time_entries").is_empty());
+    let fetched = tempo.requests("GET", "/worklogs/user/account-1");
+    assert_eq!(fetched[0].query, "limit=1000&from=2026-10-12&to=2026-10-18");
+}
+
+#[test]
+fn totals_issues_from_descriptions_and_synced_worklogs() {
+    let groups: Vec<SummaryGroup> = serde_json::from_value(summary()["groups"].clone()).unwrap();
+    let worklogs: Vec<WorkLogResult> = serde_json::from_value(worklogs()["results"].clone()).unwrap();
+
+    let totals = issue_totals(&groups, &worklogs, &KeyMatcher::default());
+
+    assert_eq!(totals.tracked.get("PROJ-4"), Some(&3600));
+    // Matched through the description of the Tempo worklog
+    assert_eq!(totals.tracked.get("PROJ-12"), Some(&5400));
+    assert_eq!(totals.tracked.get("PROJ-7"), None);
+    assert_eq!(totals.synced.get("PROJ-12"), Some(&3600));
+    assert_eq!(totals.synced.get("PROJ-7"), Some(&900));
+    assert_eq!(totals.without_key, 1800);
+}
+
+#[test]
+fn reports_descriptions_synced_to_several_issues() {
+    let groups: Vec<SummaryGroup> = serde_json::from_value(summary()["groups"].clone()).unwrap();
+    let mut worklogs = worklogs();
+    worklogs["results"][1]["description"] = json!("Fix login");
+    let worklogs: Vec<WorkLogResult> = serde_json::from_value(worklogs["results"].clone()).unwrap();
+
+    let totals = issue_totals(&groups, &worklogs, &KeyMatcher::default());
+
+    assert_eq!(totals.tracked.get("PROJ-12"), None);
+    assert_eq!(totals.tracked.get("PROJ-7"), None);
+    assert_eq!(totals.ambiguous.get("Fix login"), Some(&5400));
+    assert_eq!(totals.without_key, 1800);
+}
+
+#[tokio::test]
+async fn looks_up_parents_up_to_the_epics() {
+    let _serial = SERIAL.lock().await;
+    use_temp_dir("report_epics");
+    let tempo = MockServer::start().await;
+    tempo.mock("GET", "/worklogs/user/account-1", vec![MockResponse::json(200, worklogs())]);
+    let jira = MockServer::start().await;
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![
+            MockResponse::json(
+                200,
+                json!({ "issues": [
+                    {
+                        "key": "PROJ-12",
+                        "fields": {
+                            "summary": "Login fails",
+                            "issuetype": { "name": "Sub-task", "hierarchyLevel": -1 },
+                            "parent": { "key": "PROJ-3" }
+                        }
+                    },
+                    {
+                        "key": "PROJ-7",
+                        "fields": { "summary": "Review", "issuetype": { "name": "Task", "hierarchyLevel": 0 } }
+                    }
+                ] }),
+            ),
+            MockResponse::json(
+                200,
+                json!({ "issues": [{
+                    "key": "PROJ-3",
+                    "fields": {
+                        "summary": "Login",
+                        "issuetype": { "name": "Story", "hierarchyLevel": 0 },
+                        "parent": { "key": "PROJ-1" }
+                    }
+                }] }),
+            ),
+            MockResponse::json(
+                200,
+                json!({ "issues": [{
+                    "key": "PROJ-1",
+                    "fields": { "summary": "Accounts", "issuetype": { "name": "Epic", "hierarchyLevel": 1 } }
+                }] }),
+            ),
+        ],
+    );
+    let config = Config {
+        tempo_url: Some(tempo.url.to_string()),
+        jira_url: Some(jira.url.to_string()),
+        ..Default::default()
+    };
+    let credentials = jira_credentials();
+
+    let from = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+    let to = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    run_epic_report(&credentials, &config, from, to).await.unwrap();
+
+    let searched = jira.requests("POST", "/rest/api/3/search/jql");
+    assert_eq!(searched.len(), 3);
+    assert_eq!(searched[0].json()["jql"], "key in (\"PROJ-12\", \"PROJ-7\")");
+    assert_eq!(searched[0].json()["fields"], json!(["summary", "issuetype", "parent"]));
+    assert_eq!(searched[1].json()["jql"], "key in (\"PROJ-3\")");
+    assert_eq!(searched[2].json()["jql"], "key in (\"PROJ-1\")");
+
+    let worklogs: Vec<WorkLogResult> = serde_json::from_value(worklogs()["results"].clone()).unwrap();
+    let parents = retreive_issue_parents().unwrap();
+    assert_eq!(parents["PROJ-1"].summary, "Accounts");
+    let totals = epic_totals(&worklogs, &parents);
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0].epic.as_deref(), Some("PROJ-1"));
+    assert_eq!(totals[0].seconds, 3600);
+    assert_eq!(totals[0].issues.get("PROJ-12"), Some(&3600));
+    assert_eq!(totals[1].epic, None);
+    assert_eq!(totals[1].seconds, 900);
+    assert_eq!(totals[1].issues.get("PROJ-7"), Some(&900));
+
+    // Cached for the next report
+    run_epic_report(&credentials, &config, from, to).await.unwrap();
+    assert_eq!(jira.requests("POST", "/rest/api/3/search/jql").len(), 3);
+}
+
+#[tokio::test]
+async fn follows_the_epic_link_on_jira_server() {
+    let _serial = SERIAL.lock().await;
+    use_temp_dir("report_epic_link");
+    let jira = MockServer::start().await;
+    jira.mock(
+        "POST",
+        "/rest/tempo-timesheets/4/worklogs/search",
+        vec![MockResponse::json(
+            200,
+            json!([{
+                "issue": { "key": "PROJ-12" },
+                "started": "2026-10-12 09:00:00.000",
+                "timeSpentSeconds": 3600,
+                "comment": "Fix login"
+            }]),
+        )],
+    );
+    jira.mock(
+        "GET",
+        "/rest/api/2/field",
+        vec![MockResponse::json(
+            200,
+            json!([
+                { "id": "summary", "name": "Summary", "schema": { "type": "string" } },
+                {
+                    "id": "customfield_10008",
+                    "name": "Epic Link",
+                    "schema": { "type": "any", "custom": "com.pyxis.greenhopper.jira:gh-epic-link" }
+                }
+            ]),
+        )],
+    );
+    jira.mock(
+        "POST",
+        "/rest/api/2/search",
+        vec![
+            MockResponse::json(
+                200,
+                json!({ "startAt": 0, "total": 1, "issues": [{
+                    "key": "PROJ-12",
+                    "fields": {
+                        "summary": "Login fails",
+                        "issuetype": { "name": "Story" },
+                        "customfield_10008": "PROJ-1"
+                    }
+                }] }),
+            ),
+            MockResponse::json(
+                200,
+                json!({ "startAt": 0, "total": 1, "issues": [{
+                    "key": "PROJ-1",
+                    "fields": { "summary": "Accounts", "issuetype": { "name": "Epic" }, "customfield_10008": null }
+                }] }),
+            ),
+        ],
+    );
+    let config = Config {
+        jira_url: Some(jira.url.to_string()),
+        jira_flavour: Some(JiraFlavour::Server),
+        ..Default::default()
+    };
+    let credentials = Credentials {
+        jira_token: "jira-token".to_string(),
+        ..credentials()
+    };
+
+    let from = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+    let to = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    run_epic_report(&credentials, &config, from, to).await.unwrap();
+
+    let searched = jira.requests("POST", "/rest/api/2/search");
+    assert_eq!(searched.len(), 2);
+    assert_eq!(
+        searched[0].json()["fields"],
+        json!(["summary", "issuetype", "parent", "customfield_10008"])
+    );
+    assert_eq!(searched[1].json()["jql"], "key in (\"PROJ-1\")");
+    let parents = retreive_issue_parents().unwrap();
+    assert_eq!(epic_of("PROJ-12", &parents).as_deref(), Some("PROJ-1"));
+}
+
+fn node(parent: Option<&str>, is_epic: bool) -> IssueParent {
+    IssueParent {
+        summary: String::new(),
+        parent: parent.map(|parent| parent.to_string()),
+        is_epic,
+        checked_at: 0,
+    }
+}
+
+#[test]
+fn finds_the_epic_above_an_issue() {
+    let parents = HashMap::from([
+        ("PROJ-1".to_string(), node(None, true)),
+        ("PROJ-3".to_string(), node(Some("PROJ-1"), false)),
+        ("PROJ-12".to_string(), node(Some("PROJ-3"), false)),
+        ("PROJ-20".to_string(), node(Some("PROJ-12"), false)),
+        ("PROJ-30".to_string(), node(Some("PROJ-99"), false)),
+        ("PROJ-40".to_string(), node(None, false)),
+    ]);
+
+    assert_eq!(epic_of("PROJ-1", &parents).as_deref(), Some("PROJ-1"));
+    assert_eq!(epic_of("PROJ-3", &parents).as_deref(), Some("PROJ-1"));
+    // Sub-task, story, epic
+    assert_eq!(epic_of("PROJ-12", &parents).as_deref(), Some("PROJ-1"));
+    // Deeper than the hierarchy goes
+    assert_eq!(epic_of("PROJ-20", &parents), None);
+    // Parent missing in the lookups
+    assert_eq!(epic_of("PROJ-30", &parents), None);
+    assert_eq!(epic_of("PROJ-40", &parents), None);
+    assert_eq!(epic_of("PROJ-50", &parents), None);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use std::net::TcpListener;
+
+use chrono::{DateTime, Duration, Utc};
+use common::{
+    credentials, mock_bulk_edit, mock_toggl_and_tempo, time_entry, use_temp_dir, MockResponse, MockServer,
+};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use toggl_sync::{
+    prompts::{remaining_answers, script_answers},
+    serve::run_serve,
+    storage::{
+        config::{Config, WorklogSink},
+        webhooks::{retreive_queue, WebhookQueue},
+    },
+    sync::run_sync,
+    toggl::structs::TimeEntry,
+};
+use tokio::sync::Mutex;
+
+// The key history and queue files are process wide
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+const SECRET: &str = "webhook-secret";
+
+async fn start(name: &str) -> (String, MockServer, MockServer) {
+    use_temp_dir(name);
+    let (toggl, tempo) = mock_toggl_and_tempo(json!([])).await;
+    let config = Config {
+        toggl_url: Some(toggl.url.to_string()),
+        tempo_url: Some(tempo.url.to_string()),
+        webhook_secret: Some(SECRET.to_string()),
+        ..Default::default()
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(run_serve(credentials(), config, listener));
+    (url, toggl, tempo)
+}
+
+fn sign(body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    let signature = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!("sha256={}", signature)
+}
+
+async fn post(url: &str, body: &Value, signature: Option<String>) -> reqwest::Response {
+    let body = body.to_string();
+    let signature = signature.unwrap_or_else(|| sign(&body));
+    reqwest::Client::new()
+        .post(url)
+        .header("X-Webhook-Signature-256", signature)
+        .body(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+fn event(action: &str, entry: Value) -> Value {
+    json!({
+        "event_id": 1,
+        "metadata": { "action": action, "model": "time_entry" },
+        "payload": entry,
+    })
+}
+
+#[tokio::test]
+async fn answers_the_validation_handshake() {
+    let _serial = SERIAL.lock().await;
+    let (url, _toggl, _tempo) = start("serve_validation").await;
+
+    let body = json!({ "payload": "ping", "validation_code": "abc-123" });
+    let response = post(&url, &body, None).await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.json::<Value>().await.unwrap(), json!({ "validation_code": "abc-123" }));
+
+    let forged = post(&url, &body, Some("sha256=00".to_string())).await;
+    assert_eq!(forged.status(), 401);
+}
+
+#[tokio::test]
+async fn syncs_stopped_entries_with_a_key() {
+    let _serial = SERIAL.lock().await;
+    let (url, toggl, tempo) = start("serve_sync").await;
+    mock_bulk_edit(&toggl, "4", json!([4]), json!([]));
+
+    let running = time_entry(3, "PROJ-8 Running", "2026-10-12T09:00:00+00:00", -1, true, json!(null));
+    let stopped = time_entry(4, "PROJ-8 Deploy", "2026-10-12T10:00:00+00:00", 1200, true, json!(null));
+    let missing_key = time_entry(5, "Lunch", "2026-10-12T12:00:00+00:00", 1800, false, json!(null));
+    for (action, entry) in [("created", running), ("updated", stopped), ("updated", missing_key)] {
+        assert_eq!(post(&url, &event(action, entry), None).await.status(), 200);
+    }
+
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 1);
+    let worklog = posted[0].json();
+    assert_eq!(worklog["issueKey"], "PROJ-8");
+    assert_eq!(worklog["description"], "Deploy");
+    assert_eq!(worklog["timeSpentSeconds"], 1200);
+    let tags = toggl.requests("PATCH", "/workspaces/1/time_entries/4");
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].json()[0]["value"], json!(["PROJ-8"]));
+
+    let queue = retreive_queue().unwrap();
+    let pending = queue.pending.iter().map(|queued| queued.id).collect::<Vec<_>>();
+    assert_eq!(pending, vec![5]);
+    assert!(queue.is_synced(4));
+    assert!(!queue.is_synced(3));
+}
+
+#[tokio::test]
+async fn ignores_updates_of_entries_posted_by_sync() {
+    let _serial = SERIAL.lock().await;
+    let (url, toggl, tempo) = start("serve_after_sync").await;
+    let start = (Utc::now() - Duration::days(1)).to_rfc3339();
+    let deploy = time_entry(6, "Deploy", &start, 1200, true, json!(["PROJ-8"]));
+    toggl.mock("GET", "/me/time_entries", vec![MockResponse::json(200, json!([deploy]))]);
+    // Queued without a key, one of them will fail to post
+    for (id, description) in [(7, "Lunch"), (8, "Meeting")] {
+        let entry = time_entry(id, description, &start, 600, false, json!(null));
+        assert_eq!(post(&url, &event("updated", entry), None).await.status(), 200);
+    }
+    let config = Config {
+        toggl_url: Some(toggl.url.to_string()),
+        tempo_url: Some(tempo.url.to_string()),
+        // Without a Jira site posting to Jira fails
+        project_sinks: Some([("OPS".to_string(), WorklogSink::Jira)].into()),
+        ..Default::default()
+    };
+
+    script_answers(&[
+        "2026-10-12",
+        "2026-10-12",
+        "y",
+        // Deploy
+        "n",
+        "",
+        // Lunch
+        "OPS-2",
+        "n",
+        "Breaks",
+        // Meeting
+        "PROJ-1",
+        "n",
+        "Meetings",
+    ]);
+    run_sync(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 2);
+
+    let queue = retreive_queue().unwrap();
+    assert!(queue.is_synced(6));
+    assert!(queue.is_synced(8));
+    let pending = queue.pending.iter().map(|queued| queued.id).collect::<Vec<_>>();
+    assert_eq!(pending, vec![7]);
+
+    // Tagging by sync makes Toggl send updates for the posted entries
+    let tagged = time_entry(6, "Deploy", &start, 1200, true, json!(["PROJ-8"]));
+    assert_eq!(post(&url, &event("updated", tagged), None).await.status(), 200);
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 2);
+}
+
+#[test]
+fn forgets_synced_entries_once_they_are_old() {
+    let entry = |id: i64, start: DateTime<Utc>| {
+        serde_json::from_value::<TimeEntry>(time_entry(id, "PROJ-8 Deploy", &start.to_rfc3339(), 600, true, json!(null)))
+            .unwrap()
+    };
+    let mut queue = WebhookQueue::default();
+
+    queue.mark_synced(&entry(1, Utc::now() - Duration::days(90)));
+    queue.mark_synced(&entry(2, Utc::now() - Duration::days(2)));
+
+    assert!(!queue.is_synced(1));
+    assert!(queue.is_synced(2));
+    assert_eq!(queue.synced.len(), 1);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use common::{credentials, MockResponse, MockServer};
+use serde_json::json;
+use toggl_sync::{sink::create_worklogs, storage::config::Config, tempo::structs::Worklog};
+
+fn worklog(key: &str) -> Worklog {
+    Worklog {
+        author_account_id: "account-1".to_string(),
+        description: "Review".to_string(),
+        issue_key: key.to_string(),
+        start_date: "2026-10-12".to_string(),
+        start_time: "08:00:00".to_string(),
+        time_spent_seconds: 900,
+        billable_seconds: 900,
+        date: Utc::now(),
+        entry_ids: Vec::new(),
+    }
+}
+
+async fn setup(response: MockResponse) -> (MockServer, Config) {
+    let tempo = MockServer::start().await;
+    tempo.mock("POST", "/worklogs", vec![response]);
+    let config = Config {
+        tempo_url: Some(tempo.url.to_string()),
+        ..Default::default()
+    };
+    (tempo, config)
+}
+
+#[tokio::test]
+async fn posts_worklogs_concurrently() {
+    let delay = Duration::from_millis(300);
+    let (tempo, config) = setup(MockResponse::json(200, json!({})).with_delay(delay)).await;
+    let worklogs = ["PROJ-1", "PROJ-2", "PROJ-3", "PROJ-4"].into_iter().map(worklog).collect();
+
+    let started = Instant::now();
+    let failed = create_worklogs(&credentials(), &config, worklogs).await.unwrap();
+
+    assert!(failed.is_empty());
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 4);
+    // Posted one after another this would take four delays
+    assert!(started.elapsed() < delay * 3);
+}
+
+#[tokio::test]
+async fn returns_failed_worklogs_in_order() {
+    let (_tempo, config) = setup(MockResponse::json(500, json!({}))).await;
+    let keys = ["PROJ-1", "PROJ-2", "PROJ-3", "PROJ-4", "PROJ-5", "PROJ-6"];
+
+    let failed = create_worklogs(&credentials(), &config, keys.into_iter().map(worklog).collect())
+        .await
+        .unwrap();
+
+    let failed_keys = failed.iter().map(|log| log.issue_key.as_str()).collect::<Vec<_>>();
+    assert_eq!(failed_keys, keys);
+}
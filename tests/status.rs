@@ -0,0 +1,84 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{MockResponse, MockServer};
+use reqwest::Client;
+use serde_json::json;
+use toggl_sync::{
+    status::format_schedule_day,
+    storage::config::JiraFlavour,
+    tempo::{
+        service::get_user_schedule,
+        structs::{Holiday, ScheduleDay},
+    },
+};
+
+fn day(date: &str, required_seconds: u64, day_type: &str, holiday: Option<&str>) -> ScheduleDay {
+    ScheduleDay {
+        date: date.to_string(),
+        required_seconds,
+        day_type: day_type.to_string(),
+        holiday: holiday.map(|name| Holiday { name: name.to_string() }),
+    }
+}
+
+fn format(date: &str, schedule: &ScheduleDay, spent: u64) -> Option<String> {
+    colored::control::set_override(false);
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+    format_schedule_day(date, schedule, spent)
+}
+
+#[test]
+fn compares_logged_time_with_the_schedule() {
+    let monday = day("2026-10-12", 28800, "WORKING_DAY", None);
+    assert_eq!(format("2026-10-12", &monday, 27000), Some("Mon 7h30 of 8h required".to_string()));
+    assert_eq!(format("2026-10-12", &monday, 30600), Some("Mon 8h30 (+0h30)".to_string()));
+    assert_eq!(format("2026-10-12", &monday, 28800), Some("Mon 8h".to_string()));
+}
+
+#[test]
+fn shows_holidays_and_skips_free_days() {
+    let holiday = day("2026-10-14", 0, "HOLIDAY", Some("Founders day"));
+    assert_eq!(format("2026-10-14", &holiday, 0), Some("Wed holiday (Founders day)".to_string()));
+    let unnamed = day("2026-10-15", 0, "HOLIDAY_AND_NON_WORKING_DAY", None);
+    assert_eq!(format("2026-10-15", &unnamed, 0), Some("Thu holiday".to_string()));
+    let saturday = day("2026-10-17", 0, "NON_WORKING_DAY", None);
+    assert_eq!(format("2026-10-17", &saturday, 0), None);
+    // Time logged on a free day is all extra
+    assert_eq!(format("2026-10-17", &saturday, 3600), Some("Sat 1h (+1h)".to_string()));
+}
+
+#[tokio::test]
+async fn fetches_the_schedule_of_the_account() {
+    let tempo = MockServer::start().await;
+    tempo.mock(
+        "GET",
+        "/user-schedule/account-1",
+        vec![MockResponse::json(
+            200,
+            json!({ "results": [
+                { "date": "2026-10-12", "requiredSeconds": 28800, "type": "WORKING_DAY" },
+                { "date": "2026-10-13", "requiredSeconds": 0, "type": "HOLIDAY", "holiday": { "name": "Founders day" } }
+            ] }),
+        )],
+    );
+
+    let schedule = get_user_schedule(
+        &Client::new(),
+        &tempo.url,
+        JiraFlavour::Cloud,
+        "tempo-token",
+        "account-1",
+        "2026-10-12",
+        "2026-10-13",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(schedule.len(), 2);
+    assert_eq!(schedule[0].required_seconds, 28800);
+    assert_eq!(schedule[1].holiday.as_ref().map(|holiday| holiday.name.as_str()), Some("Founders day"));
+    let requested = tempo.requests("GET", "/user-schedule/account-1");
+    assert_eq!(requested[0].query, "from=2026-10-12&to=2026-10-13");
+    assert_eq!(requested[0].header("authorization"), Some("Bearer tempo-token"));
+}
@@ -0,0 +1,660 @@
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use common::{
+    credentials, jira_credentials, mock_bulk_edit, mock_jira_permission, mock_toggl_and_tempo, time_entry,
+    use_temp_dir, MockResponse, MockServer,
+};
+use serde_json::{json, Value};
+use toggl_sync::{
+    prompts::{remaining_answers, script_answers},
+    storage::{
+        config::{Config, JiraFlavour, NonBillableDefault, TransitionRule, WorklogSink},
+        credentials::{Credentials, TogglAuth},
+        jira::{retreive_valid_keys, store_valid_keys},
+        metadata::retreive_metadata,
+    },
+    sync::run_sync,
+};
+use tokio::sync::Mutex;
+
+// The scripted answers and the key history file are process wide
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+async fn setup(name: &str, entries: Value) -> (MockServer, MockServer, Config) {
+    use_temp_dir(name);
+    let (toggl, tempo) = mock_toggl_and_tempo(entries).await;
+    let config = Config {
+        toggl_url: Some(toggl.url.to_string()),
+        tempo_url: Some(tempo.url.to_string()),
+        ..Default::default()
+    };
+    (toggl, tempo, config)
+}
+
+fn worklog_for<'a>(worklogs: &'a [Value], key: &str) -> &'a Value {
+    worklogs
+        .iter()
+        .find(|worklog| worklog["issueKey"] == key)
+        .unwrap_or_else(|| panic!("No worklog posted for {}", key))
+}
+
+#[tokio::test]
+async fn syncs_tagged_and_prompted_entries() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "Meeting", "2026-10-12T08:00:00+00:00", 1800, false, json!(null)),
+        time_entry(2, "Fix login", "2026-10-12T09:00:00+00:00", 3600, true, json!(["PROJ-12"])),
+        time_entry(3, "Fix login", "2026-10-12T10:00:00+00:00", 1800, false, json!(["PROJ-12"])),
+    ]);
+    let (toggl, tempo, config) = setup("prompted", entries).await;
+    mock_bulk_edit(&toggl, "2,3", json!([2]), json!([{ "id": 3, "message": "Entry is locked" }]));
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+
+    script_answers(&[
+        "2026-10-12",
+        "2026-10-12",
+        // Fix login, key found in the tags
+        "n",
+        "",
+        // Meeting, key missing
+        "PROJ-1",
+        "n",
+        "Meetings",
+    ]);
+    run_sync(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let fetched = toggl.requests("GET", "/me/time_entries");
+    assert_eq!(fetched.len(), 1);
+    assert_eq!(fetched[0].query, "start_date=2026-10-12&end_date=2026-10-13");
+    assert_eq!(
+        fetched[0].header("authorization"),
+        Some("Basic dG9nZ2wtdXNlcjp0b2dnbC1wYXNzd29yZA==")
+    );
+    // Projects, clients and tags are fetched once and cached
+    assert_eq!(toggl.requests("GET", "/me/projects").len(), 1);
+    assert_eq!(toggl.requests("GET", "/me/clients").len(), 1);
+    assert_eq!(toggl.requests("GET", "/me/tags").len(), 1);
+    let cached = retreive_metadata().unwrap();
+    assert_eq!(cached.tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(), ["PROJ-12"]);
+
+    let posted = tempo.requests("POST", "/worklogs");
+    assert!(posted.iter().all(|request| request.header("authorization") == Some("Bearer tempo-token")));
+    let worklogs = posted.iter().map(|request| request.json()).collect::<Vec<_>>();
+    assert_eq!(worklogs.len(), 2);
+
+    let fix_login = worklog_for(&worklogs, "PROJ-12");
+    assert_eq!(fix_login["authorAccountId"], "account-1");
+    assert_eq!(fix_login["description"], "Fix login");
+    assert_eq!(fix_login["startDate"], "2026-10-12");
+    assert_eq!(fix_login["startTime"], "09:00:00");
+    assert_eq!(fix_login["timeSpentSeconds"], 5400);
+    assert_eq!(fix_login["billableSeconds"], 3600);
+
+    let meeting = worklog_for(&worklogs, "PROJ-1");
+    assert_eq!(meeting["description"], "Meeting");
+    assert_eq!(meeting["timeSpentSeconds"], 1800);
+    assert_eq!(meeting["billableSeconds"], 0);
+
+    let meeting_tags = toggl.requests("PATCH", "/workspaces/1/time_entries/1");
+    assert_eq!(meeting_tags.len(), 1);
+    assert_eq!(
+        meeting_tags[0].json(),
+        json!([{ "op": "add", "path": "/tags", "value": ["PROJ-1"] }])
+    );
+    let fix_login_tags = toggl.requests("PATCH", "/workspaces/1/time_entries/2,3");
+    assert_eq!(fix_login_tags.len(), 1);
+    assert_eq!(fix_login_tags[0].json()[0]["value"], json!(["PROJ-12"]));
+}
+
+#[tokio::test]
+async fn non_billable_default_uses_time_spent() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, false, json!(["PROJ-3"]))]);
+    let (_toggl, tempo, mut config) = setup("billable", entries).await;
+    config.non_billable = NonBillableDefault::TimeSpent;
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials(), &config).await.unwrap();
+
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].json()["billableSeconds"], 900);
+}
+
+#[tokio::test]
+async fn retries_rate_limited_worklogs() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-3"]))]);
+    let (toggl, tempo, config) = setup("rate_limit", entries).await;
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    tempo.mock(
+        "POST",
+        "/worklogs",
+        vec![
+            MockResponse::json(429, json!({})).with_header("Retry-After", "0"),
+            MockResponse::json(200, json!({})),
+        ],
+    );
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials(), &config).await.unwrap();
+
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 2);
+    assert_eq!(toggl.requests("PATCH", "/workspaces/1/time_entries/1").len(), 1);
+}
+
+#[tokio::test]
+async fn offers_to_submit_every_week_of_the_synced_range() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-3"]))]);
+    let (toggl, tempo, config) = setup("submit_weeks", entries).await;
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let approval = json!({
+        "period": { "from": "2026-10-05", "to": "2026-10-11" },
+        "requiredSeconds": 144000,
+        "timeSpentSeconds": 144000,
+        "status": { "key": "OPEN", "comment": null },
+        "reviewer": null
+    });
+    tempo.mock("GET", "/timesheet-approvals/user/account-1", vec![MockResponse::json(200, approval.clone())]);
+    tempo.mock(
+        "GET",
+        "/timesheet-approvals/user/account-1/reviewers",
+        vec![MockResponse::json(200, json!({ "results": [{ "accountId": "reviewer-1", "displayName": "Alice" }] }))],
+    );
+    tempo.mock("POST", "/timesheet-approvals/user/account-1/submit", vec![MockResponse::json(200, approval)]);
+
+    script_answers(&[
+        "2026-10-09",
+        "2026-10-13",
+        "n",
+        "",
+        // Submit, then confirm and skip the comment for both weeks
+        "y",
+        "y",
+        "",
+        "y",
+        "",
+    ]);
+    run_sync(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let submitted = tempo.requests("POST", "/timesheet-approvals/user/account-1/submit");
+    let periods = submitted.iter().map(|request| request.query.as_str()).collect::<Vec<_>>();
+    assert_eq!(periods, ["from=2026-10-05&to=2026-10-11", "from=2026-10-12&to=2026-10-18"]);
+}
+
+#[tokio::test]
+async fn authenticates_toggl_with_api_token() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-3"]))]);
+    let (toggl, _tempo, config) = setup("api_token", entries).await;
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let credentials = Credentials {
+        toggl_auth: Some(TogglAuth::ApiToken),
+        api_token: "secret-token".to_string(),
+        ..credentials()
+    };
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+
+    // secret-token:api_token
+    let expected = Some("Basic c2VjcmV0LXRva2VuOmFwaV90b2tlbg==");
+    assert_eq!(toggl.requests("GET", "/me/time_entries")[0].header("authorization"), expected);
+    assert_eq!(
+        toggl.requests("PATCH", "/workspaces/1/time_entries/1")[0].header("authorization"),
+        expected
+    );
+}
+
+#[tokio::test]
+async fn skips_entries_outside_selected_workspaces() {
+    let _serial = SERIAL.lock().await;
+    let mut personal = time_entry(2, "Gym", "2026-10-12T17:00:00+00:00", 3600, false, json!(["PERS-1"]));
+    personal["workspace_id"] = json!(2);
+    let entries = json!([
+        time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-3"])),
+        personal,
+    ]);
+    let (toggl, tempo, mut config) = setup("workspaces", entries).await;
+    config.workspace_ids = Some(vec![1]);
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].json()["issueKey"], "PROJ-3");
+    assert!(toggl.requests("PATCH", "/workspaces/2/time_entries/2").is_empty());
+}
+
+#[tokio::test]
+async fn rewrites_descriptions_with_the_key() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-3"])),
+        time_entry(2, "PROJ-4 Deploy", "2026-10-12T09:00:00+00:00", 600, true, json!(["PROJ-4"])),
+    ]);
+    let (toggl, _tempo, mut config) = setup("rewrite", entries).await;
+    config.rewrite_descriptions = Some(true);
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    mock_bulk_edit(&toggl, "2", json!([2]), json!([]));
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", "", "n", ""]);
+    run_sync(&credentials(), &config).await.unwrap();
+
+    let edits = toggl
+        .requests("PATCH", "/workspaces/1/time_entries/1")
+        .iter()
+        .map(|request| request.json())
+        .collect::<Vec<_>>();
+    assert!(edits.contains(&json!([{ "op": "replace", "path": "/description", "value": "PROJ-3 Review" }])));
+    // Already canonical, only tagged
+    let edits = toggl.requests("PATCH", "/workspaces/1/time_entries/2");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].json()[0]["path"], "/tags");
+}
+
+#[tokio::test]
+async fn replaces_keys_missing_in_jira() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "Fix login", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-12"])),
+        time_entry(2, "Review", "2026-10-12T09:00:00+00:00", 600, true, json!(["PRJO-12"])),
+    ]);
+    let (toggl, tempo, mut config) = setup("jira_keys", entries).await;
+    let jira = MockServer::start().await;
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![MockResponse::json(
+            200,
+            json!({ "issues": [{ "key": "PROJ-12", "fields": { "summary": "Login fails" } }] }),
+        )],
+    );
+    config.jira_url = Some(jira.url.to_string());
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    mock_bulk_edit(&toggl, "2", json!([2]), json!([]));
+    let credentials = jira_credentials();
+
+    script_answers(&[
+        "2026-10-12",
+        "2026-10-12",
+        "n",
+        "",
+        "n",
+        "",
+        // PRJO-12 is not in Jira
+        "PROJ-12: Fix login",
+    ]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let searched = jira.requests("POST", "/rest/api/3/search/jql");
+    assert_eq!(searched.len(), 1);
+    assert_eq!(searched[0].json()["jql"], "key in (\"PRJO-12\", \"PROJ-12\")");
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 2);
+    assert!(posted.iter().all(|request| request.json()["issueKey"] == "PROJ-12"));
+}
+
+#[tokio::test]
+async fn fetches_summaries_for_new_keys() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Fix login", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-12"]))]);
+    let (toggl, tempo, mut config) = setup("jira_summary", entries).await;
+    let jira = MockServer::start().await;
+    jira.mock(
+        "GET",
+        "/rest/api/3/issue/PROJ-12",
+        vec![MockResponse::json(200, json!({ "key": "PROJ-12", "fields": { "summary": "Login fails" } }))],
+    );
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![MockResponse::json(200, json!({ "issues": [{ "key": "PROJ-12" }] }))],
+    );
+    config.jira_url = Some(jira.url.to_string());
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let credentials = jira_credentials();
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let fetched = jira.requests("GET", "/rest/api/3/issue/PROJ-12");
+    assert_eq!(fetched.len(), 1);
+    assert_eq!(fetched[0].query, "fields=summary");
+    // The summary is only the default for the key history, the worklog keeps the Toggl description
+    assert_eq!(tempo.requests("POST", "/worklogs")[0].json()["description"], "Fix login");
+}
+
+#[tokio::test]
+async fn syncs_when_jira_is_unreachable() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Fix login", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-12"]))]);
+    let (toggl, tempo, mut config) = setup("jira_offline", entries).await;
+    config.jira_url = Some("http://127.0.0.1:9".to_string());
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let credentials = jira_credentials();
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 1);
+}
+
+#[tokio::test]
+async fn replaces_resolved_issues() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Hotfix", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-5"]))]);
+    let (toggl, tempo, mut config) = setup("jira_resolved", entries).await;
+    let jira = MockServer::start().await;
+    let done = json!({ "name": "Done", "statusCategory": { "key": "done" } });
+    let open = json!({ "name": "In Progress", "statusCategory": { "key": "indeterminate" } });
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![
+            MockResponse::json(200, json!({ "issues": [{ "key": "PROJ-5", "fields": { "status": done } }] })),
+            MockResponse::json(200, json!({ "issues": [{ "key": "PROJ-6", "fields": { "status": open } }] })),
+        ],
+    );
+    mock_jira_permission(&jira);
+    config.jira_url = Some(jira.url.to_string());
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let credentials = jira_credentials();
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", "", "Other key", "PROJ-6"]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let searched = jira.requests("POST", "/rest/api/3/search/jql");
+    assert_eq!(searched.len(), 2);
+    assert_eq!(searched[0].json()["fields"], json!(["summary", "status", "resolution"]));
+    assert_eq!(searched[1].json()["jql"], "key in (\"PROJ-6\")");
+    let permissions = jira.requests("GET", "/rest/api/3/mypermissions");
+    assert_eq!(permissions.len(), 1);
+    assert_eq!(permissions[0].query, "issueKey=PROJ-6&permissions=WORK_ON_ISSUES");
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].json()["issueKey"], "PROJ-6");
+}
+
+#[tokio::test]
+async fn skips_jira_checks_for_recently_validated_keys() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Hotfix", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-5"]))]);
+    let (toggl, tempo, mut config) = setup("jira_valid_cache", entries).await;
+    let jira = MockServer::start().await;
+    let open = json!({ "name": "In Progress", "statusCategory": { "key": "indeterminate" } });
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![MockResponse::json(200, json!({ "issues": [{ "key": "PROJ-5", "fields": { "status": open } }] }))],
+    );
+    mock_jira_permission(&jira);
+    config.jira_url = Some(jira.url.to_string());
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let credentials = jira_credentials();
+    // Validated long ago, so checked again
+    let stale = Utc::now().timestamp() - 31 * 24 * 60 * 60;
+    store_valid_keys(&[("PROJ-5".to_string(), stale)].into()).unwrap();
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    assert_eq!(jira.requests("POST", "/rest/api/3/search/jql").len(), 1);
+    assert_eq!(jira.requests("GET", "/rest/api/3/mypermissions").len(), 1);
+    let checked_at = retreive_valid_keys().unwrap()["PROJ-5"];
+    assert!(checked_at > stale);
+
+    // The first entry is synced now, a new one on the same key is not checked again
+    let later = time_entry(2, "Hotfix", "2026-10-12T10:00:00+00:00", 600, true, json!(["PROJ-5"]));
+    toggl.mock("GET", "/me/time_entries", vec![MockResponse::json(200, json!([later]))]);
+    mock_bulk_edit(&toggl, "2", json!([2]), json!([]));
+    script_answers(&["2026-10-12", "2026-10-12", "n"]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    assert_eq!(jira.requests("POST", "/rest/api/3/search/jql").len(), 1);
+    assert_eq!(jira.requests("GET", "/rest/api/3/mypermissions").len(), 1);
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 2);
+}
+
+#[tokio::test]
+async fn posts_worklogs_to_the_sink_of_the_project() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "Review", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-3"])),
+        time_entry(2, "Restart workers", "2026-10-12T09:30:00+00:00", 1200, true, json!(["OPS-2"])),
+    ]);
+    let (toggl, tempo, mut config) = setup("sinks", entries).await;
+    let jira = MockServer::start().await;
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![MockResponse::json(200, json!({ "issues": [{ "key": "PROJ-3" }, { "key": "OPS-2" }] }))],
+    );
+    jira.mock("POST", "/rest/api/3/issue/OPS-2/worklog", vec![MockResponse::json(201, json!({}))]);
+    config.jira_url = Some(jira.url.to_string());
+    config.project_sinks = Some([("OPS".to_string(), WorklogSink::Jira)].into());
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    mock_bulk_edit(&toggl, "2", json!([2]), json!([]));
+    let credentials = jira_credentials();
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", "", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].json()["issueKey"], "PROJ-3");
+    let jira_posted = jira.requests("POST", "/rest/api/3/issue/OPS-2/worklog");
+    assert_eq!(jira_posted.len(), 1);
+    assert_eq!(
+        jira_posted[0].json(),
+        json!({
+            "started": "2026-10-12T09:30:00.000+0000",
+            "timeSpentSeconds": 1200,
+            "comment": {
+                "type": "doc",
+                "version": 1,
+                "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "Restart workers" }] }]
+            }
+        })
+    );
+    assert_eq!(toggl.requests("PATCH", "/workspaces/1/time_entries/2").len(), 1);
+}
+
+#[tokio::test]
+async fn matches_lowercase_keys_of_allowed_projects() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "proj-7 Upgrade UTF-8 parser", "2026-10-12T08:00:00+00:00", 1800, false, json!(null)),
+    ]);
+    let (toggl, tempo, mut config) = setup("key_projects", entries).await;
+    config.key_projects = Some(vec!["PROJ".to_string()]);
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let posted = tempo.requests("POST", "/worklogs");
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].json()["issueKey"], "PROJ-7");
+    assert_eq!(posted[0].json()["description"], "Upgrade UTF-8 parser");
+    let tags = toggl.requests("PATCH", "/workspaces/1/time_entries/1");
+    assert_eq!(tags[0].json()[0]["value"], json!(["PROJ-7"]));
+}
+
+#[tokio::test]
+async fn takes_keys_from_descriptions_and_rejects_typed_keys_outside_allowed_projects() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "PROJ-12 Fix login", "2026-10-12T08:00:00+00:00", 900, false, json!(null)),
+        time_entry(2, "OPS-3 Rotate certificates", "2026-10-12T09:00:00+00:00", 600, false, json!(null)),
+    ]);
+    let (toggl, tempo, mut config) = setup("description_keys", entries).await;
+    config.key_projects = Some(vec!["PROJ".to_string()]);
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    mock_bulk_edit(&toggl, "2", json!([2]), json!([]));
+
+    script_answers(&[
+        "2026-10-12",
+        "2026-10-12",
+        // OPS-3 Rotate certificates, OPS is not allowed so it asks
+        "ops-3",
+        "PROJ-1",
+        "n",
+        "Maintenance",
+        // PROJ-12 Fix login, key found in the description
+        "n",
+        "",
+    ]);
+    run_sync(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let posted = tempo.requests("POST", "/worklogs").iter().map(|request| request.json()).collect::<Vec<_>>();
+    assert_eq!(posted.len(), 2);
+    assert_eq!(worklog_for(&posted, "PROJ-12")["description"], "Fix login");
+    assert_eq!(worklog_for(&posted, "PROJ-1")["description"], "OPS-3 Rotate certificates");
+}
+
+#[tokio::test]
+async fn syncs_to_jira_server_with_the_tempo_plugin() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([time_entry(1, "Fix login", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-12"]))]);
+    let (toggl, _tempo, mut config) = setup("jira_server", entries).await;
+    let jira = MockServer::start().await;
+    jira.mock(
+        "GET",
+        "/rest/api/2/issue/PROJ-12",
+        vec![MockResponse::json(200, json!({ "key": "PROJ-12", "fields": { "summary": "Login fails" } }))],
+    );
+    jira.mock(
+        "POST",
+        "/rest/api/2/search",
+        vec![MockResponse::json(200, json!({ "issues": [{ "key": "PROJ-12" }], "startAt": 0, "total": 1 }))],
+    );
+    jira.mock(
+        "GET",
+        "/rest/api/2/mypermissions",
+        vec![MockResponse::json(200, json!({ "permissions": { "WORK_ON_ISSUES": { "havePermission": true } } }))],
+    );
+    jira.mock(
+        "GET",
+        "/rest/tempo-core/1/user/schedule",
+        vec![MockResponse::json(
+            200,
+            json!({ "days": [{ "date": "2026-10-12", "requiredSeconds": 28800, "type": "WORKING_DAY" }] }),
+        )],
+    );
+    jira.mock("POST", "/rest/tempo-timesheets/4/worklogs", vec![MockResponse::json(200, json!([]))]);
+    config.jira_url = Some(jira.url.to_string());
+    config.jira_flavour = Some(JiraFlavour::Server);
+    config.tempo_url = None;
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    let credentials = Credentials {
+        jira_token: "pat-token".to_string(),
+        ..credentials()
+    };
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let searched = jira.requests("POST", "/rest/api/2/search");
+    assert_eq!(searched.len(), 1);
+    assert_eq!(searched[0].json()["startAt"], 0);
+    assert_eq!(searched[0].header("authorization"), Some("Bearer pat-token"));
+    let posted = jira.requests("POST", "/rest/tempo-timesheets/4/worklogs");
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].header("authorization"), Some("Bearer pat-token"));
+    let worklog = posted[0].json();
+    assert_eq!(worklog["worker"], "account-1");
+    assert_eq!(worklog["originTaskId"], "PROJ-12");
+    assert_eq!(worklog["started"], "2026-10-12 08:00:00.000");
+    assert_eq!(worklog["timeSpentSeconds"], 900);
+    assert_eq!(worklog["comment"], "Fix login");
+    let schedule = jira.requests("GET", "/rest/tempo-core/1/user/schedule");
+    assert_eq!(schedule[0].query, "user=account-1&from=2026-10-12&to=2026-10-12");
+}
+
+#[tokio::test]
+async fn transitions_issues_once_time_is_logged() {
+    let _serial = SERIAL.lock().await;
+    let entries = json!([
+        time_entry(1, "Fix login", "2026-10-12T08:00:00+00:00", 900, true, json!(["PROJ-12"])),
+        time_entry(2, "Review", "2026-10-12T09:00:00+00:00", 900, true, json!(["PROJ-3"])),
+    ]);
+    let (toggl, tempo, mut config) = setup("jira_transitions", entries).await;
+    let jira = MockServer::start().await;
+    let to_do = json!({ "name": "To Do", "statusCategory": { "key": "new" } });
+    let in_review = json!({ "name": "In Review", "statusCategory": { "key": "indeterminate" } });
+    jira.mock(
+        "POST",
+        "/rest/api/3/search/jql",
+        vec![MockResponse::json(
+            200,
+            json!({ "issues": [
+                { "key": "PROJ-12", "fields": { "status": to_do } },
+                { "key": "PROJ-3", "fields": { "status": in_review } }
+            ] }),
+        )],
+    );
+    mock_jira_permission(&jira);
+    jira.mock(
+        "GET",
+        "/rest/api/3/issue/PROJ-12",
+        vec![MockResponse::json(200, json!({ "key": "PROJ-12", "fields": { "summary": "Login fails", "status": to_do } }))],
+    );
+    jira.mock(
+        "GET",
+        "/rest/api/3/issue/PROJ-3",
+        vec![MockResponse::json(200, json!({ "key": "PROJ-3", "fields": { "summary": "Review", "status": in_review } }))],
+    );
+    jira.mock(
+        "GET",
+        "/rest/api/3/issue/PROJ-12/transitions",
+        vec![MockResponse::json(
+            200,
+            json!({ "transitions": [
+                { "id": "11", "name": "Close", "to": { "name": "Done" } },
+                { "id": "21", "name": "Start work", "to": { "name": "In Progress" } }
+            ] }),
+        )],
+    );
+    jira.mock("POST", "/rest/api/3/issue/PROJ-12/transitions", vec![MockResponse::json(204, json!(null))]);
+    config.jira_url = Some(jira.url.to_string());
+    config.transition_rules = Some(HashMap::from([(
+        "PROJ".to_string(),
+        TransitionRule {
+            from: "To Do".to_string(),
+            to: "In Progress".to_string(),
+        },
+    )]));
+    mock_bulk_edit(&toggl, "1", json!([1]), json!([]));
+    mock_bulk_edit(&toggl, "2", json!([2]), json!([]));
+    let credentials = jira_credentials();
+
+    script_answers(&["2026-10-12", "2026-10-12", "n", "", "n", ""]);
+    run_sync(&credentials, &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    assert_eq!(tempo.requests("POST", "/worklogs").len(), 2);
+    let transitioned = jira.requests("POST", "/rest/api/3/issue/PROJ-12/transitions");
+    assert_eq!(transitioned.len(), 1);
+    assert_eq!(transitioned[0].json(), json!({ "transition": { "id": "21" } }));
+    // Already past the "from" status of the rule
+    assert!(jira.requests("GET", "/rest/api/3/issue/PROJ-3/transitions").is_empty());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{credentials, time_entry, use_temp_dir, MockResponse, MockServer};
+use serde_json::json;
+use toggl_sync::{
+    prompts::{remaining_answers, script_answers},
+    storage::config::Config,
+    timer::{run_start, run_stop},
+};
+use tokio::sync::Mutex;
+
+// The scripted answers and the key history file are process wide
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+async fn setup(name: &str) -> (MockServer, Config) {
+    use_temp_dir(name);
+    let toggl = MockServer::start().await;
+    let config = Config {
+        toggl_url: Some(toggl.url.to_string()),
+        workspace_ids: Some(vec![1]),
+        ..Default::default()
+    };
+    (toggl, config)
+}
+
+#[tokio::test]
+async fn starts_a_tagged_timer() {
+    let _serial = SERIAL.lock().await;
+    let (toggl, config) = setup("timer_start").await;
+    toggl.mock(
+        "POST",
+        "/workspaces/1/time_entries",
+        vec![MockResponse::json(
+            200,
+            time_entry(9, "Deploy", "2026-10-12T10:00:00+00:00", -1, false, json!(["PROJ-8"])),
+        )],
+    );
+
+    script_answers(&["PROJ-8: Deployments", "Deploy"]);
+    run_start(&credentials(), &config).await.unwrap();
+    assert!(remaining_answers().is_empty());
+
+    let created = toggl.requests("POST", "/workspaces/1/time_entries");
+    assert_eq!(created.len(), 1);
+    let body = created[0].json();
+    assert_eq!(body["description"], "Deploy");
+    assert_eq!(body["tags"], json!(["PROJ-8"]));
+    assert_eq!(body["duration"], -1);
+    assert_eq!(body["workspace_id"], 1);
+}
+
+#[tokio::test]
+async fn stops_the_running_timer() {
+    let _serial = SERIAL.lock().await;
+    let (toggl, config) = setup("timer_stop").await;
+    toggl.mock(
+        "GET",
+        "/me/time_entries/current",
+        vec![MockResponse::json(
+            200,
+            time_entry(9, "Deploy", "2026-10-12T10:00:00+00:00", -1, false, json!(["PROJ-8"])),
+        )],
+    );
+    toggl.mock(
+        "PATCH",
+        "/workspaces/1/time_entries/9/stop",
+        vec![MockResponse::json(
+            200,
+            time_entry(9, "Deploy", "2026-10-12T10:00:00+00:00", 1500, false, json!(["PROJ-8"])),
+        )],
+    );
+
+    run_stop(&credentials(), &config).await.unwrap();
+
+    assert_eq!(toggl.requests("PATCH", "/workspaces/1/time_entries/9/stop").len(), 1);
+}
+
+#[tokio::test]
+async fn stop_without_a_running_timer() {
+    let _serial = SERIAL.lock().await;
+    let (toggl, config) = setup("timer_idle").await;
+    toggl.mock("GET", "/me/time_entries/current", vec![MockResponse::json(200, json!(null))]);
+
+    run_stop(&credentials(), &config).await.unwrap();
+
+    assert!(toggl.requests("PATCH", "/workspaces/1/time_entries/9/stop").is_empty());
+}
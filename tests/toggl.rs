@@ -0,0 +1,144 @@
+mod common;
+
+use chrono::Utc;
+use common::{time_entry, MockResponse, MockServer};
+use reqwest::Client;
+use serde_json::json;
+use toggl_sync::toggl::{
+    service::{merge_filter_entries, tag_entries},
+    structs::{Project, TimeEntry, TogglClient, TogglMetadata},
+};
+
+fn metadata(fetched_at: i64) -> TogglMetadata {
+    TogglMetadata {
+        fetched_at,
+        projects: vec![
+            Project {
+                id: 5,
+                workspace_id: 1,
+                client_id: Some(9),
+                name: "Platform".to_string(),
+            },
+            Project {
+                id: 6,
+                workspace_id: 1,
+                client_id: None,
+                name: "Internal".to_string(),
+            },
+        ],
+        clients: vec![TogglClient {
+            id: 9,
+            wid: 1,
+            name: "Acme".to_string(),
+        }],
+        tags: Vec::new(),
+    }
+}
+
+#[test]
+fn labels_projects_with_their_client() {
+    let metadata = metadata(Utc::now().timestamp());
+
+    assert_eq!(metadata.project_label(Some(5)), Some("Platform / Acme".to_string()));
+    assert_eq!(metadata.project_label(Some(6)), Some("Internal".to_string()));
+    assert_eq!(metadata.project_label(Some(7)), None);
+    assert_eq!(metadata.project_label(None), None);
+}
+
+#[test]
+fn refreshes_metadata_after_the_interval() {
+    let hour = 60 * 60;
+    let now = Utc::now().timestamp();
+
+    assert!(!metadata(now - hour).is_stale(24));
+    assert!(metadata(now - 25 * hour).is_stale(24));
+    assert!(metadata(now).is_stale(0));
+    // Fetched in the future, the clock moved
+    assert!(metadata(now + hour).is_stale(24));
+}
+
+#[test]
+fn merges_entries_of_different_projects() {
+    let mut other_project = time_entry(2, "Fix login", "2026-10-12T10:00:00+00:00", 1800, false, json!(null));
+    other_project["project_id"] = json!(6);
+    let entries = serde_json::from_value::<Vec<TimeEntry>>(json!([
+        time_entry(1, "Fix login", "2026-10-12T09:00:00+00:00", 3600, true, json!(null)),
+        other_project,
+    ]))
+    .unwrap();
+
+    let merged = merge_filter_entries(entries);
+
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].duration, 5400);
+    // The merged entry belongs to neither project
+    assert_eq!(merged[0].project_id, None);
+}
+
+#[test]
+fn sums_billable_time_of_merged_entries() {
+    let entries = serde_json::from_value::<Vec<TimeEntry>>(json!([
+        time_entry(1, "Fix login", "2026-10-12T09:00:00+00:00", 3600, true, json!(null)),
+        time_entry(2, "Fix login", "2026-10-12T11:00:00+00:00", 1800, false, json!(null)),
+        time_entry(3, "Fix login", "2026-10-12T13:00:00+00:00", 900, true, json!(null)),
+    ]))
+    .unwrap();
+
+    let merged = merge_filter_entries(entries);
+
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].duration, 6300);
+    assert_eq!(merged[0].billable_duration, 4500);
+}
+
+#[test]
+fn merges_entries_on_the_local_day() {
+    // Only this test depends on the time zone
+    unsafe { std::env::set_var("TZ", "Europe/Amsterdam") };
+    let entries = serde_json::from_value::<Vec<TimeEntry>>(json!([
+        time_entry(1, "Fix login", "2026-10-12T21:00:00+00:00", 1800, true, json!(null)),
+        time_entry(2, "Fix login", "2026-10-12T22:30:00+00:00", 1800, true, json!(null)),
+        time_entry(3, "Fix login", "2026-10-13T07:00:00+00:00", 3600, true, json!(null)),
+    ]))
+    .unwrap();
+
+    let merged = merge_filter_entries(entries);
+
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].duration, 1800);
+    assert_eq!(merged[1].duration, 5400);
+}
+
+#[test]
+fn keeps_entries_without_a_start() {
+    let mut without_start = time_entry(2, "Fix login", "2026-10-12T10:00:00+00:00", 1800, true, json!(null));
+    without_start["start"] = json!("");
+    let entries = serde_json::from_value::<Vec<TimeEntry>>(json!([
+        time_entry(1, "Fix login", "2026-10-12T09:00:00+00:00", 3600, true, json!(null)),
+        without_start,
+    ]))
+    .unwrap();
+
+    let merged = merge_filter_entries(entries);
+
+    assert_eq!(merged.iter().map(|entry| entry.duration).sum::<i64>(), 5400);
+}
+
+#[tokio::test]
+async fn keeps_tagging_after_a_failed_chunk() {
+    let toggl = MockServer::start().await;
+    let ids = (1..=150).collect::<Vec<i64>>();
+    let second_chunk = ids[100..].iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    toggl.mock(
+        "PATCH",
+        &format!("/workspaces/1/time_entries/{}", second_chunk),
+        vec![MockResponse::json(200, json!({ "success": ids[100..], "failure": [] }))],
+    );
+
+    let result = tag_entries(&Client::new(), &toggl.url, "toggl-user", "toggl-password", 1, &ids, "PROJ-1")
+        .await
+        .unwrap();
+
+    assert_eq!(result.success, ids[100..]);
+    assert_eq!(result.failure.iter().map(|failure| failure.id).collect::<Vec<_>>(), ids[..100]);
+}